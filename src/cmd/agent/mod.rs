@@ -0,0 +1,268 @@
+use crate::{
+    config::{SignerEntry, get_config_path, read_config},
+    contract::{
+        agent::{AGENT_SOCK_ENV, AgentRequest, AgentResponse, signer_identity},
+        signer::Signer,
+    },
+};
+use clap::Args;
+use nix::sys::stat::{self, Mode};
+use std::{
+    os::unix::{fs::PermissionsExt, net::UnixStream},
+    path::{Path, PathBuf},
+    process::exit,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixListener,
+    time::Instant,
+};
+
+/// Options for `zaph agent`.
+#[derive(Args)]
+pub struct AgentArgs {
+    /// Key IDs from the config whose signers should be unlocked (repeatable).
+    /// Defaults to every key entry that has a signer.
+    #[arg(long = "key-id")]
+    key_ids: Vec<String>,
+
+    /// Path of the Unix socket to listen on.
+    /// Defaults to `$XDG_RUNTIME_DIR/zaphenath/agent.sock` (or a private temp directory).
+    #[arg(long)]
+    socket: Option<String>,
+
+    /// Exit after this many seconds without a request, forgetting the unlocked keys.
+    #[arg(long)]
+    idle_timeout: Option<u64>,
+}
+
+/// An unlocked signer, under the identity clients ask for it by.
+type Unlocked = (SignerEntry, Arc<dyn Signer>);
+type Signers = Arc<Vec<Unlocked>>;
+
+pub async fn handle_agent_command(args: AgentArgs) {
+    if let Err(e) = run_agent(args).await {
+        eprintln!("{e}");
+        exit(1);
+    }
+}
+
+async fn run_agent(args: AgentArgs) -> Result<(), anyhow::Error> {
+    let signers = Arc::new(unlock_signers(&args.key_ids)?);
+    if signers.is_empty() {
+        return Err(anyhow::anyhow!("❌ No signers to unlock in the config"));
+    }
+
+    // Only a directory of our own is created and locked down; a socket path the user gives
+    // lives wherever they chose, and only the socket itself is restricted
+    let (socket, private_dir) = match args.socket {
+        Some(socket) => (PathBuf::from(socket), None),
+        None => {
+            let dir = default_socket_dir();
+            (dir.join("agent.sock"), Some(dir))
+        }
+    };
+    let listener = bind(&socket, private_dir.as_deref())?;
+
+    println!(
+        "{AGENT_SOCK_ENV}={}; export {AGENT_SOCK_ENV};",
+        socket.display()
+    );
+    eprintln!(
+        "✅ Agent holding {} key(s), listening on {}",
+        signers.len(),
+        socket.display()
+    );
+
+    let result = serve(
+        listener,
+        signers,
+        args.idle_timeout.map(Duration::from_secs),
+    )
+    .await;
+    let _ = std::fs::remove_file(&socket);
+    result
+}
+
+/// Loads (and unlocks) the signers of the selected key entries, once per distinct signer.
+fn unlock_signers(key_ids: &[String]) -> Result<Vec<Unlocked>, anyhow::Error> {
+    let config = read_config(&get_config_path())?;
+    let entries = if key_ids.is_empty() {
+        config
+            .keys
+            .iter()
+            .filter(|entry| entry.key_source().is_ok())
+            .collect::<Vec<_>>()
+    } else {
+        key_ids
+            .iter()
+            .map(|key_id| config.find_key(key_id))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let mut signers: Vec<Unlocked> = Vec::new();
+    for entry in entries {
+        let source = entry.key_source()?;
+        let identity = signer_identity(&source);
+        if signers.iter().any(|(known, _)| *known == identity) {
+            continue;
+        }
+        let signer = source.load_local_signer()?;
+        eprintln!(
+            "🔓 Unlocked {:#x} for key '{}'",
+            signer.address(),
+            entry.key_id
+        );
+        signers.push((identity, signer));
+    }
+    Ok(signers)
+}
+
+/// The agent's own directory for its default socket.
+fn default_socket_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("zaphenath"),
+        None => std::env::temp_dir().join(format!("zaphenath-{}", std::process::id())),
+    }
+}
+
+/// Binds the socket, creating `private_dir` (the agent's own directory, if the socket is in
+/// one) so only we can enter it. The socket itself is owner-only from the start. Refuses to
+/// replace the socket of an agent that is still running.
+fn bind(socket: &Path, private_dir: Option<&Path>) -> Result<UnixListener, anyhow::Error> {
+    if let Some(dir) = private_dir {
+        std::fs::create_dir_all(dir)?;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| anyhow::anyhow!("❌ Failed to make {} private: {e}", dir.display()))?;
+    }
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(anyhow::anyhow!(
+                "❌ An agent is already listening on {}",
+                socket.display()
+            ));
+        }
+        std::fs::remove_file(socket)?;
+    }
+
+    // Nobody else may connect between creating the socket and restricting it
+    let umask = stat::umask(Mode::from_bits_truncate(0o177));
+    let listener = UnixListener::bind(socket);
+    stat::umask(umask);
+    let listener = listener
+        .map_err(|e| anyhow::anyhow!("❌ Failed to listen on {}: {e}", socket.display()))?;
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answers requests until Ctrl-C or until no request arrived for `idle_timeout`.
+async fn serve(
+    listener: UnixListener,
+    signers: Signers,
+    idle_timeout: Option<Duration>,
+) -> Result<(), anyhow::Error> {
+    let last_activity = Arc::new(Mutex::new(Instant::now()));
+
+    loop {
+        let idle = async {
+            match idle_timeout {
+                Some(timeout) => {
+                    let deadline = *last_activity.lock().unwrap() + timeout;
+                    tokio::time::sleep_until(deadline).await
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                *last_activity.lock().unwrap() = Instant::now();
+                let signers = signers.clone();
+                let last_activity = last_activity.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, signers, last_activity).await {
+                        eprintln!("⚠️ Agent connection failed: {e}");
+                    }
+                });
+            }
+            _ = idle => {
+                // A request may have come in while we slept; only stop if it didn't.
+                if let Some(timeout) = idle_timeout
+                    && last_activity.lock().unwrap().elapsed() >= timeout
+                {
+                    eprintln!("⏱️ Agent idle for {}s, exiting", timeout.as_secs());
+                    return Ok(());
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("👋 Agent stopped");
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: tokio::net::UnixStream,
+    signers: Signers,
+    last_activity: Arc<Mutex<Instant>>,
+) -> Result<(), anyhow::Error> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let response = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(request) => answer(request, &signers).await,
+            Err(e) => AgentResponse::Error(format!("invalid request: {e}")),
+        };
+        *last_activity.lock().unwrap() = Instant::now();
+
+        let mut reply = serde_json::to_string(&response)?;
+        reply.push('\n');
+        writer.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+async fn answer(request: AgentRequest, signers: &Signers) -> AgentResponse {
+    let find = |address| {
+        signers
+            .iter()
+            .map(|(_, signer)| signer)
+            .find(|signer| signer.address() == address)
+            .ok_or_else(|| format!("no key for {address:#x}"))
+    };
+
+    let result = match request {
+        AgentRequest::Address { signer } => {
+            let address = signers
+                .iter()
+                .find(|(identity, _)| *identity == signer)
+                .map(|(_, signer)| signer.address());
+            return AgentResponse::Address(address);
+        }
+        AgentRequest::SignTransaction { address, tx } => match find(address) {
+            Ok(signer) => signer
+                .sign_transaction(&tx)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        },
+        AgentRequest::SignMessage { address, message } => match find(address) {
+            Ok(signer) => signer
+                .sign_message(&message.0)
+                .await
+                .map(web3::types::Bytes)
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        },
+    };
+
+    match result {
+        Ok(signed) => AgentResponse::Signed(signed),
+        Err(e) => AgentResponse::Error(e),
+    }
+}
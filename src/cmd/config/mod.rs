@@ -1,15 +1,19 @@
 use crate::{
-    config::{add_key, get_config_path, view_config},
+    cmd::types::{NetworkArgs, SignerArgs, key_location_from_args, key_name},
+    config::{ConfigFile, add_key, get_config_path, lock_config, view_config, write_config},
     contract::{
-        network,
-        types::{ContractSpecs, KeyData},
+        key_id,
+        types::{KeyData, Role},
     },
 };
 use clap::Subcommand;
+use std::str::FromStr;
+use web3::types::H256;
 
 /// Actions for managing the local Zaphenath configuration file.
 /// This file stores details about your keys, contract addresses, and network settings.
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
 pub enum ConfigAction {
     /// View the current content of the local Zaphenath configuration file.
     /// This will print the JSON representation of your configured keys and their details.
@@ -19,36 +23,54 @@ pub enum ConfigAction {
     /// To create a key on-chain, use `zaph contract create-key`.
     Add {
         /// A unique, human-readable identifier for this key in your local config.
-        #[arg(long)]
-        key_id: String,
+        #[arg(long, required_unless_present = "key_hash")]
+        key_id: Option<String>,
 
-        /// The Ethereum address of the deployed Zaphenath smart contract.
-        #[arg(long)]
-        contract_address: String,
+        /// The key's raw bytes32 on-chain ID, for a key created by another client or naming
+        /// scheme. The key is saved under `--key-id` if given, or under the hash itself.
+        #[arg(long, value_parser = key_id::parse, conflicts_with = "salted_id")]
+        key_hash: Option<H256>,
 
-        /// The file path to the private key of the owner of this key.
-        /// The private key should be hex-encoded.
+        /// The Ethereum address of the deployed Zaphenath smart contract.
+        /// Defaults to the active profile's contract address.
         #[arg(long)]
-        private_key_path: String,
+        contract_address: Option<String>,
 
-        /// The blockchain network associated with this key (e.g., "mainnet", "sepolia", "anvil").
-        #[arg(long)]
-        network: String,
+        /// The signer owning this key. Defaults to the active profile's signer.
+        #[command(flatten)]
+        signer: SignerArgs,
 
-        /// Optional: The RPC URL for connecting to the blockchain node.
-        /// If not provided, a default URL for the specified network will be used.
-        #[arg(long)]
-        rpc_url: Option<String>,
+        /// The blockchain network and RPC URL associated with this key.
+        #[command(flatten)]
+        network_specs: NetworkArgs,
 
         /// The default inactivity timeout in seconds for this key.
         /// This is the duration after which the key might become accessible if not pinged.
         #[arg(long, value_parser = clap::value_parser!(u64))]
         timeout: u64,
 
+        /// Seconds between the daemon's pings of this key.
+        /// Defaults to the daemon's `--ping-fraction` of the timeout.
+        #[arg(long)]
+        ping_interval: Option<u64>,
+
         /// Optional: The Ethereum address of the key's owner.
-        /// If not provided, it will be derived from the private key at the given path.
+        /// If not provided, it will be derived from the signer.
         #[arg(long)]
         owner: Option<String>,
+
+        /// The key was created with `--salted-id`: derive its on-chain ID from the owner's signer.
+        #[arg(long, requires = "key_id")]
+        salted_id: bool,
+
+        /// Track a key someone else owns, as a watch-only entry with our role on it
+        /// (Writer, Reader or None). Needs `--owner`; the signer becomes optional.
+        #[arg(long, requires = "owner", value_parser = Role::from_str)]
+        role: Option<Role>,
+
+        /// We may ping the watch-only key on the owner's behalf; the daemon will do so.
+        #[arg(long, requires = "role")]
+        can_ping: bool,
     },
     /// Initialize a new local Zaphenath configuration file.
     /// If the file already exists, this will fail unless `--force` is passed.
@@ -69,35 +91,52 @@ pub async fn handle_config_command(action: ConfigAction) {
         ConfigAction::View => view_config(&path),
         ConfigAction::Add {
             key_id,
+            key_hash,
             contract_address,
-            private_key_path,
+            signer,
             timeout,
-            network,
-            rpc_url,
+            ping_interval,
+            network_specs,
             owner,
+            salted_id,
+            role,
+            can_ping,
         } => {
-            let ctx =
-                match network::build_network_context(rpc_url.as_deref(), Some(network.as_ref())) {
-                    Ok(c) => c,
-                    Err(_) => {
-                        let e = anyhow::anyhow!("❌ Missing network or rpc-url");
-                        eprintln!("{e}");
-                        std::process::exit(1);
-                    }
-                };
+            let (ctx, contract_addr, key_source) = match key_location_from_args(
+                contract_address.as_deref(),
+                &signer,
+                &network_specs,
+            ) {
+                Ok(location) => location,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+            // A watch-only entry may have no signer of ours at all
+            let no_signer_given = matches!(signer.entry().key_source(), Ok(None));
+            let key_source = match key_source {
+                Ok(source) => Some(source),
+                Err(_) if role.is_some() && no_signer_given => None,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
 
             if let Err(e) = add_key(
                 &path,
-                &mut ContractSpecs {
-                    ctx,
-                    contract_addr: contract_address,
-                    priv_key_path: private_key_path,
-                    priv_key: None,
-                },
+                &ctx,
+                &contract_addr,
+                key_source,
                 KeyData {
-                    id: key_id,
+                    id: key_name(key_id.as_deref(), key_hash),
                     timeout,
+                    ping_interval,
                     owner,
+                    scheme: key_id::Scheme::from_args(key_hash, salted_id),
+                    role,
+                    can_ping,
                 },
             )
             .await
@@ -107,7 +146,7 @@ pub async fn handle_config_command(action: ConfigAction) {
             };
         }
         ConfigAction::Init { force } => {
-            use std::fs::{create_dir_all, write};
+            use std::fs::create_dir_all;
             use std::path::Path;
 
             let path = get_config_path();
@@ -126,7 +165,15 @@ pub async fn handle_config_command(action: ConfigAction) {
                 std::process::exit(1);
             }
 
-            if let Err(e) = write(&path, "[]") {
+            let _lock = match lock_config(&path) {
+                Ok(l) => l,
+                Err(e) => {
+                    eprintln!("❌ {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            if let Err(e) = write_config(&path, &ConfigFile::default()) {
                 eprintln!("❌ Failed to write config file: {e}");
                 std::process::exit(1);
             }
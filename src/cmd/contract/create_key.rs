@@ -1,33 +1,38 @@
 use crate::{
     cmd::types::GasAndConfirmArgs,
-    config::{get_config_path, read_config, write_config},
+    config::{KeyEntry, SignerEntry, get_config_path, read_config, update_config},
     contract::{
-        create_key,
+        create_key, key_id,
         types::{ContractSpecs, GasSpecs},
     },
 };
-use serde_json::json;
-use web3::{
-    signing::{Key, SecretKeyRef},
-    types::Address,
-};
 
 pub async fn handle_create_key(
     key_id: &str,
+    scheme: key_id::Scheme,
     data: &str,
     timeout: u64,
     contract_specs: &mut ContractSpecs,
     mock: bool,
     gas_confirm: &GasAndConfirmArgs,
 ) -> Result<(), anyhow::Error> {
-    match contract_specs.load_private_key_if_missing() {
-        Ok(r) => r,
+    let owner_addr = match contract_specs.signer_address() {
+        Ok(addr) => addr,
         Err(err) => {
             let e = anyhow::anyhow!("❌load private key failed. Reason: {:?}", err);
             return Err(e);
         }
     };
 
+    // Refuse a clashing name while nothing has been sent yet
+    if read_config(&get_config_path())?.contains_key(key_id) {
+        return Err(anyhow::anyhow!(
+            "❌ Key '{key_id}' already exists in config, pick another --key-id"
+        ));
+    }
+
+    let key_hash = scheme.key_hash(key_id, || contract_specs.signer()).await?;
+
     // 🧪 Skip on-chain interaction if mock is enabled
     // tx_result will now directly contain the H256 on success or an Error
     if mock {
@@ -36,7 +41,7 @@ pub async fn handle_create_key(
     } else {
         create_key::create_key_on_chain(
             contract_specs,
-            key_id,
+            key_hash,
             data,
             timeout,
             gas_confirm.yes,
@@ -50,66 +55,43 @@ pub async fn handle_create_key(
     }?; // Use '?' to propagate errors from create_key_on_chain
 
     // If we reach here, the transaction was successful (or mock was enabled)
-    // 🧠 Derive owner address from private key
-    let sk = match contract_specs.get_private_key() {
-        Some(sec) => sec,
-        None => {
-            // This case should ideally not happen if load_private_key_if_missing was successful
-            // and no external factors cleared the priv_key.
-            // However, added as a fallback/safety.
-            match contract_specs.load_private_key_if_missing() {
-                Ok(sec) => sec,
-                Err(e) => {
-                    let e =
-                        anyhow::anyhow!("Failed to load private key for owner derivation: {:?}", e);
-                    return Err(e);
-                }
-            }
-        }
-    };
-
-    let owner_addr: Address = SecretKeyRef::new(sk).address();
+    // 🧠 Owner is the signer, resolved before the transaction was sent
     let owner_addr_str = format!("{:#x}", owner_addr);
 
-    // ✅ Update config only on success
-    let mut config = match read_config(&get_config_path()) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            let e = anyhow::anyhow!("❌ Failed to read config: {e:?}");
-            return Err(e);
-        }
+    let new_key = KeyEntry {
+        key_id: key_id.to_string(),
+        key_hash: (scheme != key_id::Scheme::Plain).then(|| format!("{:#x}", key_hash)),
+        contract_address: contract_specs.contract_addr.clone(),
+        signer: SignerEntry::from(&contract_specs.key_source),
+        owner: Some(owner_addr_str),
+        network: contract_specs.ctx.network.clone(),
+        rpc_url: Some(contract_specs.ctx.rpc_url.clone()),
+        chain_id: contract_specs.ctx.chain_id,
+        timeout: Some(timeout),
+        ping_interval: None,
+        custodians: vec![],
+        last_ping_timestamp: None,
+        data: None,
+        role: None,
+        can_ping: false,
+        health: None,
     };
 
-    let new_key = json!({
-        "key_id": key_id,
-        "contract_address": contract_specs.contract_addr,
-        "private_key_path": contract_specs.priv_key_path,
-        "owner": owner_addr_str,
-        "network": contract_specs.ctx.network,
-        "rpc_url": contract_specs.ctx.rpc_url,
-        "timeout": timeout,
-        "custodians": []
-    });
-
-    if let Some(arr) = config.as_array_mut() {
-        if arr
-            .iter()
-            .any(|e| e["key_id"].as_str().is_some_and(|k| k == key_id))
-        {
-            let e = anyhow::anyhow!("⚠️ Key already exists in config. Skipping save.");
-            Err(e)
-        } else {
-            arr.push(new_key);
-            if let Err(e) = write_config(&get_config_path(), &config) {
-                let e = anyhow::anyhow!("❌ Failed to write config: {e:?}");
-                Err(e)
-            } else {
-                println!("✅ Key config saved locally");
-                Ok(())
-            }
+    // ✅ Update config only on success. The key is on-chain by now, so an entry added
+    // meanwhile only skips the save
+    let saved = update_config(&get_config_path(), |config| {
+        if config.contains_key(key_id) {
+            return Ok(false);
         }
+        config.keys.push(new_key);
+        Ok(true)
+    })
+    .map_err(|e| anyhow::anyhow!("❌ Failed to save key config: {e}"))?;
+
+    if saved {
+        println!("✅ Key config saved locally");
     } else {
-        let e = anyhow::anyhow!("❌ Invalid config format");
-        Err(e)
+        eprintln!("⚠️ Key '{key_id}' already exists in config. Skipping save.");
     }
+    Ok(())
 }
@@ -0,0 +1,83 @@
+use crate::{
+    cmd::types::{NetworkArgs, SignerArgs},
+    config::load_active_profile,
+    contract::{key_id, read::full_key_on_chain},
+};
+use std::str::FromStr;
+use web3::types::Address;
+
+pub async fn handle_key_hash(
+    label: &str,
+    salted_id: bool,
+    owner: Option<&str>,
+    contract_address: Option<&str>,
+    signer_args: &SignerArgs,
+    network_specs: &NetworkArgs,
+) -> Result<(), anyhow::Error> {
+    let profile = load_active_profile()?;
+
+    // The signer is only needed to derive a salted ID, or to stand in for a missing --owner
+    let signer = if salted_id || owner.is_none() {
+        match signer_args.resolve(profile.as_ref()) {
+            Ok(source) => Some(source.load_signer()?),
+            Err(e) if salted_id => return Err(e),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+
+    let owner = owner
+        .map(|owner| {
+            Address::from_str(owner)
+                .map_err(|_| anyhow::anyhow!("❌ Invalid owner address '{owner}'"))
+        })
+        .transpose()?;
+
+    let key_hash = match (&signer, salted_id) {
+        (Some(signer), true) => {
+            // The salt comes from the signer's secret, so it only gives the owner's ID if the
+            // signer is the owner
+            if let Some(owner) = owner
+                && signer.address() != owner
+            {
+                return Err(anyhow::anyhow!(
+                    "❌ A salted key ID must be derived by the owner's signer ({owner:#x}), not {:#x}",
+                    signer.address()
+                ));
+            }
+            let secret = key_id::owner_secret(signer.as_ref()).await?;
+            key_id::salted(&secret, label)
+        }
+        _ => key_id::plain(label),
+    };
+    println!("🔑 Key hash: {:#x}", key_hash);
+
+    let owner = match (owner, &signer) {
+        (Some(owner), _) => owner,
+        (None, Some(signer)) => signer.address(),
+        (None, None) => return Ok(()),
+    };
+    let full_key = key_id::full_key(key_hash, owner);
+    println!("👤 Owner:    {:#x}", owner);
+    println!("🧮 Full key: {:#x}", full_key);
+
+    // Cross-check against the contract when we know where it is
+    let Some(contract_addr) = contract_address
+        .map(str::to_string)
+        .or_else(|| profile.as_ref()?.contract_address.clone())
+    else {
+        return Ok(());
+    };
+    let ctx = network_specs.resolve(profile.as_ref())?;
+    let on_chain = full_key_on_chain(&ctx, &contract_addr, key_hash, owner).await?;
+    if on_chain == full_key {
+        println!("✅ Matches getFullKey on {contract_addr}");
+    } else {
+        eprintln!(
+            "⚠️ getFullKey on {contract_addr} returned {:#x}; the contract's value is authoritative",
+            on_chain
+        );
+    }
+    Ok(())
+}
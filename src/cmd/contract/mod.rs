@@ -1,17 +1,23 @@
 mod create_key;
 mod delete_key;
+mod key_hash;
 mod ping;
 mod read;
 mod remove_custodian;
 mod set_custodian;
 mod update;
-use super::types::{GasAndConfirmArgs, NetworkArgs};
-use crate::contract::{network, types::ContractSpecs};
+use super::types::{
+    AdHocKeyArgs, GasAndConfirmArgs, KeyArgs, NetworkArgs, OutputArgs, PayloadArgs, SignerArgs,
+    TargetKey, contract_specs_from_args, key_name,
+};
+use crate::contract::key_id;
 use clap::Subcommand;
+use web3::types::H256;
 
 /// Available actions for interacting with the Zaphenath smart contract.
 /// These commands enable key management (create, update, delete), liveness signaling (ping),
 /// data retrieval (read), and access control management (set/remove custodian).
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
 #[derive(Subcommand)]
 pub enum ContractAction {
     /// Create a new key on the Zaphenath contract.
@@ -19,13 +25,23 @@ pub enum ContractAction {
     CreateKey {
         /// Unique identifier for the key to be created.
         /// This ID will be hashed on-chain to identify the key.
-        #[arg(long)]
-        key_id: String,
+        #[arg(long, required_unless_present = "key_hash")]
+        key_id: Option<String>,
 
-        /// The encrypted data (hex-encoded bytes) to associate with the key.
-        /// This data becomes readable by custodians after the timeout.
-        #[arg(long)]
-        data: String, // Encrypted string data (hex-encoded)
+        /// Use this raw bytes32 on-chain ID instead of hashing `--key-id`.
+        /// The key is saved under `--key-id` if given, or under the hash itself.
+        #[arg(long, value_parser = key_id::parse, conflicts_with = "salted_id")]
+        key_hash: Option<H256>,
+
+        /// Derive the on-chain ID from the key ID and a secret only the owner's signer can
+        /// produce, so the key can't be found on-chain by guessing its name.
+        /// Custodians are given the resulting hash when they are set.
+        #[arg(long, requires = "key_id")]
+        salted_id: bool,
+
+        /// The data to store, as ready-made hex or a file to encrypt for custodians.
+        #[command(flatten)]
+        payload: PayloadArgs,
 
         /// The timeout duration in seconds.
         /// If no ping is received within this period, the key becomes accessible to custodians.
@@ -33,13 +49,13 @@ pub enum ContractAction {
         timeout: u64,
 
         /// The address of the deployed Zaphenath smart contract.
+        /// Defaults to the active profile's contract address.
         #[arg(long)]
-        contract_address: String,
+        contract_address: Option<String>,
 
-        /// Path to the file containing the private key (hex-encoded) of the key's owner.
-        /// This key is used to sign the transaction.
-        #[arg(long)]
-        private_key_path: String,
+        /// The key's owner, which signs the transaction. Defaults to the active profile's signer.
+        #[command(flatten)]
+        signer: SignerArgs,
 
         /// (Internal) Skips actual on-chain interaction, useful for testing.
         #[arg(long, hide = true)]
@@ -57,21 +73,58 @@ pub enum ContractAction {
     /// Delete an existing key from the Zaphenath contract.
     /// This operation removes the key and its associated data from the contract.
     DeleteKey {
-        /// The ID of the key to delete.
-        #[arg(long)]
-        key_id: String,
+        /// The key to delete.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
 
         /// Gas and confirmation control arguments.
         #[command(flatten)]
         gas_confirm: GasAndConfirmArgs,
     },
 
+    /// Compute a key's on-chain ID offline, and its full storage key when the owner is known.
+    /// With a contract address, the full key is cross-checked against the contract's pure
+    /// `getFullKey`.
+    KeyHash {
+        /// The key ID to hash.
+        #[arg(long)]
+        key_id: String,
+
+        /// Compute the salted ID (see `create-key --salted-id`); needs the owner's signer.
+        #[arg(long)]
+        salted_id: bool,
+
+        /// The key's owner. Defaults to the signer's address, if one is available.
+        #[arg(long)]
+        owner: Option<String>,
+
+        /// The contract to cross-check against. Defaults to the active profile's contract address.
+        #[arg(long)]
+        contract_address: Option<String>,
+
+        /// The owner's signer, for salted IDs or when `--owner` is not given.
+        #[command(flatten)]
+        signer: SignerArgs,
+
+        /// Network used for the cross-check.
+        #[command(flatten)]
+        network_specs: NetworkArgs,
+    },
+
     /// Ping an existing key to reset its inactivity timeout.
     /// Regular pings keep the key's data private until the owner becomes inactive.
     PingKey {
-        /// The ID of the key to ping.
-        #[arg(long)]
-        key_id: String,
+        /// The key to ping.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
 
         /// (Internal) Skips actual on-chain interaction, useful for testing.
         #[arg(long, hide = true)]
@@ -85,26 +138,46 @@ pub enum ContractAction {
     /// Read the data associated with a key.
     /// Data is only readable by the owner or authorized custodians after timeout.
     ReadKey {
-        /// The ID of the key to read.
-        #[arg(long)]
-        key_id: String,
+        /// The key to read.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
 
-        /// Attempt to decode the output bytes as a UTF-8 string for human readability.
-        /// If the data is not valid UTF-8, it will be printed as raw bytes.
+        /// How to output the data. Packed payloads are unpacked and verified first.
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Open the encrypted envelope made with `--encrypt-for` and output the plaintext.
+        /// Public-key envelopes are opened with the signer's private key, which must be one of
+        /// the recipients and local: a key file, env variable or HD path (`--private-key-path`,
+        /// `--private-key-env`, `--hd-path`, or the key entry's own). External signers and the
+        /// key agent never hand out the private key, so they can't open envelopes.
         #[arg(long)]
-        decode: bool,
+        decrypt: bool,
+
+        /// For passphrase-encrypted data, read the passphrase from the first line of this
+        /// file instead of prompting for it.
+        #[arg(long, requires = "decrypt")]
+        passphrase_file: Option<String>,
     },
 
     /// Update the data and/or timeout for an existing key.
     /// Only the key's owner or an authorized writer can perform this action.
     UpdateKey {
-        /// The ID of the key to update.
-        #[arg(long)]
-        key_id: String,
+        /// The key to update.
+        #[command(flatten)]
+        key: KeyArgs,
 
-        /// The new encrypted data (hex-encoded bytes) to set for the key.
-        #[arg(long)]
-        data: String,
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
+
+        /// The new data to set, as ready-made hex or a file to encrypt for custodians.
+        #[command(flatten)]
+        payload: PayloadArgs,
 
         /// The new timeout duration in seconds for the key.
         #[arg(long)]
@@ -122,9 +195,13 @@ pub enum ContractAction {
     /// Set or update access permissions for a custodian on a specific key.
     /// Custodians are external users who gain access to the key's data under defined conditions.
     SetCustodian {
-        /// The ID of the key for which to set the custodian.
-        #[arg(long)]
-        key_id: String,
+        /// The key for which to set the custodian.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
 
         /// The Ethereum address of the user to set as a custodian.
         #[arg(long)]
@@ -151,9 +228,13 @@ pub enum ContractAction {
     /// Remove a custodian's access permissions from a key.
     /// This revokes any previously assigned roles and ping rights for the specified user.
     RemoveCustodian {
-        /// The ID of the key from which to remove the custodian.
-        #[arg(long)]
-        key_id: String,
+        /// The key from which to remove the custodian.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        /// Describe the key on the command line instead of (or on top of) its config entry.
+        #[command(flatten)]
+        target: AdHocKeyArgs,
 
         /// The Ethereum address of the custodian to remove.
         #[arg(long)]
@@ -169,36 +250,42 @@ pub async fn handle_contract_command(action: &ContractAction) {
     match action {
         ContractAction::CreateKey {
             key_id,
-            data,
+            key_hash,
+            salted_id,
+            payload,
             timeout,
             network_specs,
             contract_address,
-            private_key_path,
+            signer,
             mock,
             gas_confirm,
         } => {
-            let ctx = match network::build_network_context(
-                network_specs.rpc_url.as_deref(),
-                network_specs.network.as_deref(),
+            let mut contract_specs = match contract_specs_from_args(
+                contract_address.as_deref(),
+                signer,
+                network_specs,
             ) {
-                Ok(c) => c,
-                Err(_) => {
-                    let e = anyhow::anyhow!("❌ Missing network or rpc-url");
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let data = match payload.data_hex() {
+                Ok(data) => data,
+                Err(e) => {
                     eprintln!("{e}");
                     std::process::exit(1);
                 }
             };
 
             if let Err(e) = create_key::handle_create_key(
-                key_id,
-                data,
+                &key_name(key_id.as_deref(), *key_hash),
+                key_id::Scheme::from_args(*key_hash, *salted_id),
+                &data,
                 *timeout,
-                &mut ContractSpecs {
-                    ctx,
-                    contract_addr: contract_address.to_string(),
-                    priv_key_path: private_key_path.to_string(),
-                    priv_key: None,
-                },
+                &mut contract_specs,
                 *mock,
                 gas_confirm,
             )
@@ -210,42 +297,99 @@ pub async fn handle_contract_command(action: &ContractAction) {
         }
 
         ContractAction::DeleteKey {
-            key_id,
+            key,
+            target,
             gas_confirm,
         } => {
-            if let Err(e) = delete_key::handle_delete_key(key_id, gas_confirm).await {
+            if let Err(e) =
+                delete_key::handle_delete_key(&resolve_target(target, key), gas_confirm).await
+            {
                 eprintln!("{e}");
                 std::process::exit(1);
             }
         }
 
-        ContractAction::PingKey {
+        ContractAction::KeyHash {
             key_id,
+            salted_id,
+            owner,
+            contract_address,
+            signer,
+            network_specs,
+        } => {
+            if let Err(e) = key_hash::handle_key_hash(
+                key_id,
+                *salted_id,
+                owner.as_deref(),
+                contract_address.as_deref(),
+                signer,
+                network_specs,
+            )
+            .await
+            {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+
+        ContractAction::PingKey {
+            key,
+            target,
             mock,
             gas_confirm,
         } => {
-            if let Err(e) = ping::handle_ping_key(key_id, *mock, gas_confirm).await {
+            if let Err(e) =
+                ping::handle_ping_key(&resolve_target(target, key), *mock, gas_confirm).await
+            {
                 eprintln!("{e}");
                 std::process::exit(1);
             }
         }
 
-        ContractAction::ReadKey { key_id, decode } => {
-            if let Err(e) = read::handle_read_key(key_id, *decode).await {
+        ContractAction::ReadKey {
+            key,
+            target,
+            output,
+            decrypt,
+            passphrase_file,
+        } => {
+            if let Err(e) = read::handle_read_key(
+                &resolve_target(target, key),
+                output,
+                *decrypt,
+                passphrase_file.as_deref(),
+            )
+            .await
+            {
                 eprintln!("{e}");
                 std::process::exit(1);
             }
         }
 
         ContractAction::UpdateKey {
-            key_id,
-            data,
+            key,
+            target,
+            payload,
             timeout,
             mock,
             gas_confirm,
         } => {
-            if let Err(e) =
-                update::handle_update_key(key_id, data, *timeout, *mock, gas_confirm).await
+            let data = match payload.data_hex() {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+
+            if let Err(e) = update::handle_update_key(
+                &resolve_target(target, key),
+                &data,
+                *timeout,
+                *mock,
+                gas_confirm,
+            )
+            .await
             {
                 eprintln!("{e}");
                 std::process::exit(1);
@@ -253,14 +397,15 @@ pub async fn handle_contract_command(action: &ContractAction) {
         }
 
         ContractAction::SetCustodian {
-            key_id,
+            key,
+            target,
             user_address,
             role,
             can_ping,
             gas_confirm,
         } => {
             if let Err(e) = set_custodian::handle_set_custodian(
-                key_id,
+                &resolve_target(target, key),
                 user_address,
                 role, // Pass the role as a string, it will be parsed in handle_set_custodian
                 *can_ping,
@@ -274,12 +419,17 @@ pub async fn handle_contract_command(action: &ContractAction) {
         }
 
         ContractAction::RemoveCustodian {
-            key_id,
+            key,
+            target,
             user_address,
             gas_confirm,
         } => {
-            if let Err(e) =
-                remove_custodian::handle_remove_custodian(key_id, user_address, gas_confirm).await
+            if let Err(e) = remove_custodian::handle_remove_custodian(
+                &resolve_target(target, key),
+                user_address,
+                gas_confirm,
+            )
+            .await
             {
                 eprintln!("{e}");
                 std::process::exit(1);
@@ -287,3 +437,14 @@ pub async fn handle_contract_command(action: &ContractAction) {
         }
     }
 }
+
+/// Resolves the key a command acts on, exiting with the error if it can't be.
+fn resolve_target(target: &AdHocKeyArgs, key: &KeyArgs) -> TargetKey {
+    match target.resolve(key) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,84 +1,91 @@
 use crate::{
-    config::{get_config_path, read_config},
+    cmd::types::{OutputArgs, TargetKey},
+    config::KeyAction,
     contract::{
-        read::read_key_on_chain,
-        types::{ContractSpecs, NetworkContext},
+        ecies, padding, passphrase, read::read_key_on_chain, shamir, signer::LocalKeySigner,
+        types::KeySource,
     },
 };
-use serde_json::Value;
+use std::sync::Arc;
 
-pub async fn handle_read_key(key_id: &str, decode: bool) -> Result<(), anyhow::Error> {
-    // ✅ Update config only on success
-    let config_value = match read_config(&get_config_path()) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            let e = anyhow::anyhow!("❌ Failed to read config: {e:?}");
-            return Err(e);
-        }
-    };
-
-    let key_array = config_value
-        .as_array()
-        .ok_or_else(|| anyhow::anyhow!("❌ Invalid config format"))?;
-
-    let key_entry = key_array
-        .iter()
-        .find(|entry| entry.get("key_id").and_then(Value::as_str) == Some(key_id))
-        .ok_or_else(|| anyhow::anyhow!("❌ Key '{}' not found in config", key_id))?;
+/// Reads a key's data. With `decrypt` set, passphrase-encrypted data is opened with the
+/// passphrase (from `passphrase_file` or a prompt), and envelopes for public keys with the
+/// signer's private key, which must be local (a key file, env variable or HD path).
+pub async fn handle_read_key(
+    target: &TargetKey,
+    output: &OutputArgs,
+    decrypt: bool,
+    passphrase_file: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    let key_entry = &target.entry;
+    key_entry.ensure_allows(KeyAction::Read)?;
+    let key_id = key_entry.key_id.as_str();
 
     // Extract needed fields
-    let (contract_addr, rpc_url, owner) = (
-        key_entry
-            .get("contract_address")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing contract_address"))?,
-        key_entry
-            .get("rpc_url")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing rpc_url"))?,
-        key_entry
-            .get("owner")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing owner address"))?,
-    );
-
-    let network = key_entry
-        .get("network")
-        .and_then(Value::as_str)
-        .map(str::to_string);
-    let priv_key_path = key_entry
-        .get("private_key_path")
-        .and_then(Value::as_str)
-        .map(str::to_string)
-        .ok_or_else(|| anyhow::anyhow!("Missing private_key_path"))?;
+    let mut specs = key_entry.contract_specs()?;
+    let key_hash = key_entry.key_hash()?;
+    let owner = key_entry.owner()?;
 
-    let mut specs = ContractSpecs {
-        ctx: NetworkContext {
-            rpc_url: rpc_url.to_string(),
-            network,
-        },
-        contract_addr: contract_addr.to_string(),
-        priv_key_path,
-        priv_key: None,
+    // A local key that may have to open an envelope is unlocked once, and also makes the call
+    let local_signer = if decrypt {
+        local_signer(&specs.key_source)?.map(Arc::new)
+    } else {
+        None
     };
+    if let Some(signer) = &local_signer {
+        specs.signer = Some(signer.clone());
+    }
+
+    let mut result = read_key_on_chain(&mut specs, key_hash, owner).await?;
+    // Only data whose padding header checks out is stripped, anything else is left as stored
+    if padding::is_padded(&result.0) {
+        let stored = result.0.len();
+        result.0 = padding::unpad(&result.0)?;
+        eprintln!("🧱 Stripped padding ({stored} → {} bytes)", result.0.len());
+    }
 
-    let result = read_key_on_chain(&mut specs, key_id, owner).await?;
+    if decrypt && passphrase::is_sealed(&result.0) {
+        let passphrase = passphrase::read_passphrase(
+            passphrase_file,
+            &format!("🔐 Passphrase for key '{key_id}'"),
+            false,
+        )?;
+        result.0 = passphrase::open(&result.0, &passphrase)?;
+    } else if decrypt {
+        // Opening an envelope needs the private key itself, which only local sources give us
+        let Some(signer) = local_signer else {
+            return Err(anyhow::anyhow!(
+                "❌ Key '{key_id}' signs through an external signer, which can't open envelopes. \
+                 Pass a local key with --private-key-path, --private-key-env or --hd-path"
+            ));
+        };
+        let secret = signer.secret_key().secret_bytes();
 
-    if decode {
-        match std::str::from_utf8(&result.0) {
-            Ok(s) => {
-                println!("{}", s);
-            }
-            Err(e) => {
-                let e = anyhow::anyhow!(
-                    "⚠️ Data is not valid UTF-8. Use --decode only if the content is encoded as UTF-8: {e:?}"
-                );
-                return Err(e);
-            }
+        if shamir::is_split(&result.0) {
+            // A share on its own is meaningless, it's output for `recover combine`
+            let (share, threshold) = shamir::open_share(&result.0, &secret)?;
+            eprintln!(
+                "🧩 This is one share of the secret; combine {threshold} of them with `zaph recover combine`"
+            );
+            return output.emit(&share);
         }
-    } else {
-        println!("0x{}", hex::encode(&result.0));
+        result.0 = ecies::open(&result.0, &secret)?;
+    } else if ecies::is_sealed(&result.0)
+        || shamir::is_split(&result.0)
+        || passphrase::is_sealed(&result.0)
+    {
+        eprintln!("🔒 The data is encrypted, pass --decrypt to open it");
     }
 
-    Ok(())
+    output.emit(&result.0)
+}
+
+/// The signer of a local key source, or `None` for signers holding the key elsewhere.
+fn local_signer(source: &KeySource) -> Result<Option<LocalKeySigner>, anyhow::Error> {
+    Ok(Some(match source {
+        KeySource::File(path) => LocalKeySigner::from_file(path)?,
+        KeySource::Env(var) => LocalKeySigner::from_env(var)?,
+        KeySource::HdPath(path) => LocalKeySigner::from_mnemonic(path)?,
+        KeySource::External { .. } | KeySource::Command(_) => return Ok(None),
+    }))
 }
@@ -1,87 +1,38 @@
 use crate::{
-    cmd::types::GasAndConfirmArgs,
-    config::{get_config_path, read_config, write_config},
+    cmd::types::{GasAndConfirmArgs, TargetKey},
+    config::{CustodianEntry, KeyAction},
     contract::{
         set_custodian::set_custodian_on_chain,
-        types::{ContractSpecs, CustodianData, GasSpecs, NetworkContext, Role},
+        types::{CustodianData, GasSpecs, Role},
     },
 };
-use serde_json::{Value, json};
 use std::str::FromStr; // Needed for FromStr trait on Role
 
 pub async fn handle_set_custodian(
-    key_id: &str,
+    target: &TargetKey,
     user_address: &str,
     role_str: &str, // Role as a string from CLI
     can_ping: bool,
     gas_confirm: &GasAndConfirmArgs,
 ) -> Result<(), anyhow::Error> {
-    // 1. Load config
-    let config_path = get_config_path();
-    let mut config_value = match read_config(&config_path) {
-        Ok(cfg) => cfg,
-        Err(err) => {
-            let e = anyhow::anyhow!("❌ Failed to read config: {err}");
-            return Err(e);
-        }
-    };
-
-    // 2. Find the key entry
-    let key_array = config_value
-        .as_array_mut() // Need mutable access to update it later
-        .ok_or_else(|| anyhow::anyhow!("❌ Invalid config format. Expected array of keys."))?;
-
-    let key_entry_index = key_array
-        .iter()
-        .position(|entry| entry.get("key_id").and_then(Value::as_str) == Some(key_id))
-        .ok_or_else(|| anyhow::anyhow!("❌ Key with ID '{}' not found in config", key_id))?;
-
-    let key_entry = &mut key_array[key_entry_index];
+    // 1. Take the key entry, from the config or the command line
+    let key_entry = &target.entry;
+    key_entry.ensure_allows(KeyAction::ManageCustodians)?;
 
-    // 3. Parse Role from string
+    // 2. Parse Role from string
     let role = Role::from_str(role_str)?;
 
-    // 4. Build ContractSpecs from config
-    let (contract_addr, priv_key_path, rpc_url, owner_address) = (
-        key_entry
-            .get("contract_address")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing 'contract_address' for key '{}'", key_id))?,
-        key_entry
-            .get("private_key_path")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing 'private_key_path' for key '{}'", key_id))?,
-        key_entry
-            .get("rpc_url")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing 'rpc_url' for key '{}'", key_id))?,
-        key_entry
-            .get("owner")
-            .and_then(Value::as_str)
-            .ok_or_else(|| anyhow::anyhow!("Missing 'owner' address for key '{}'", key_id))?,
-    );
-
-    let network = key_entry
-        .get("network")
-        .and_then(Value::as_str)
-        .map(str::to_string);
-
-    let mut contract_specs = ContractSpecs {
-        ctx: NetworkContext {
-            rpc_url: rpc_url.to_string(),
-            network,
-        },
-        contract_addr: contract_addr.to_string(),
-        priv_key_path: priv_key_path.to_string(),
-        priv_key: None, // Will be loaded by set_custodian_on_chain
-    };
+    // 3. Build ContractSpecs from the entry
+    let mut contract_specs = key_entry.contract_specs()?;
+    let key_hash = key_entry.key_hash()?;
+    let owner_address = key_entry.owner()?.to_string();
 
-    // 5. Call on-chain function and wait for confirmation
+    // 4. Call on-chain function and wait for confirmation
     let _tx_hash = set_custodian_on_chain(
         // Assign to _tx_hash as it's not directly used after this
         &mut contract_specs, // Pass mutable reference
-        key_id,
-        owner_address,
+        key_hash,
+        &owner_address,
         &CustodianData {
             // Pass the CustodianData struct
             address: user_address.to_string(),
@@ -97,36 +48,40 @@ pub async fn handle_set_custodian(
     )
     .await?; // Use '?' to propagate errors from set_custodian_on_chain
 
-    // If we reach here, the transaction was successful on-chain.
-    // 6. Update local config
-    // Ensure "custodians" field exists and is an array. If not, initialize it.
-    if !key_entry["custodians"].is_array() {
-        key_entry["custodians"] = json!([]);
+    // What the custodian needs to find the key, which may not be derivable from its name.
+    println!("📨 Share with the custodian:");
+    println!("   key hash: {:#x}", key_hash);
+    println!("   owner:    {owner_address}");
+    println!("   contract: {}", contract_specs.contract_addr);
+    if let Some(chain_id) = contract_specs.ctx.chain_id {
+        println!("   chain id: {chain_id}");
     }
 
-    let custodians_array = key_entry["custodians"]
-        .as_array_mut()
-        .expect("Expected 'custodians' to be an array after initialization");
+    // If we reach here, the transaction was successful on-chain.
+    // 5. Update local config
+    let new_custodian = CustodianEntry {
+        address: user_address.to_lowercase(),
+        role,
+        can_ping,
+    };
 
-    let new_custodian = json!({
-        "address": user_address.to_lowercase(),
-        "role": role_str.to_lowercase(),
-        "can_ping": can_ping,
+    let result = target.update_config(|key_entry| {
+        // Check if custodian already exists and update, or push new
+        if let Some(existing) = key_entry
+            .custodians
+            .iter_mut()
+            .find(|c| c.address == new_custodian.address)
+        {
+            *existing = new_custodian;
+            println!("✅ Updated custodian '{}' in local config.", user_address);
+        } else {
+            key_entry.custodians.push(new_custodian);
+            println!("✅ Added custodian '{}' to local config.", user_address);
+        }
+        Ok(())
     });
 
-    // Check if custodian already exists and update, or push new
-    if let Some(existing_index) = custodians_array
-        .iter()
-        .position(|c| c["address"].as_str() == Some(&user_address.to_lowercase()))
-    {
-        custodians_array[existing_index] = new_custodian;
-        println!("✅ Updated custodian '{}' in local config.", user_address);
-    } else {
-        custodians_array.push(new_custodian);
-        println!("✅ Added custodian '{}' to local config.", user_address);
-    }
-
-    if let Err(err) = write_config(&config_path, &config_value) {
+    if let Err(err) = result {
         let e =
             anyhow::anyhow!("⚠️ Custodian set on-chain but failed to update local config: {err}");
         Err(e)
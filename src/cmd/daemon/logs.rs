@@ -1,13 +1,21 @@
+use super::state::DaemonFiles;
 use std::fs;
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::process::Command;
 
-pub fn show_logs() {
-    let log_path = ".zaphenathd.log";
+pub fn show_logs(config_path: &Path) {
+    let files = match DaemonFiles::for_config(config_path) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let log_path = files.log.as_path();
 
-    if !Path::new(log_path).exists() {
-        eprintln!("⚠️ Log file not found.");
+    if !log_path.exists() {
+        eprintln!("⚠️ Log file not found at {}.", log_path.display());
         return;
     }
 
@@ -29,8 +37,8 @@ pub fn show_logs() {
     // Fallback: Print the log contents line-by-line
     match fs::File::open(log_path) {
         Ok(file) => {
-            println!("📜 Showing log file: {}\n", log_path);
-            for line in io::BufReader::new(file).lines().flatten() {
+            println!("📜 Showing log file: {}\n", log_path.display());
+            for line in io::BufReader::new(file).lines().map_while(Result::ok) {
                 println!("{line}");
             }
         }
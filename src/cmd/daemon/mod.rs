@@ -1,8 +1,13 @@
 pub mod logs;
+pub mod pidfile;
 pub mod run;
+pub mod schedule;
+pub mod state;
+pub mod status;
 pub mod stop;
-use crate::cmd::types::GasAndConfirmArgs;
+use crate::{cmd::types::GasAndConfirmArgs, config::get_config_path};
 use clap::Subcommand;
+use std::path::PathBuf;
 
 /// Background daemon for automated Zaph key management.
 /// The daemon periodically pings configured keys to keep them active.
@@ -10,13 +15,25 @@ use clap::Subcommand;
 #[derive(Subcommand)]
 pub enum DaemonAction {
     /// Start the Zaph daemon to periodically ping keys and maintain their activity.
+    /// Each key is pinged when due, at a share of its timeout (or its own `ping_interval`),
+    /// and the daemon sleeps until the next key is due.
     /// Can run in foreground (default) or background (detached) mode.
     Run {
-        /// Interval (in seconds) between automatic ping attempts.
-        /// Each cycle attempts to ping all keys listed in the local config file.
-        #[arg(long, help_heading = "Timing")]
+        /// The longest the daemon sleeps (in seconds) before re-reading the config.
+        /// Also the ping interval of keys with no timeout or `ping_interval`.
+        #[arg(long, default_value_t = 3600, help_heading = "Timing")]
         interval: u64,
 
+        /// Share of each key's timeout to wait between its pings, in (0, 1].
+        /// The default pings twice per timeout.
+        #[arg(long, default_value_t = 0.5, value_parser = schedule::parse_fraction, help_heading = "Timing")]
+        ping_fraction: f64,
+
+        /// How many keys may be pinged at the same time.
+        /// Keys signed by the same account still get sequential nonces.
+        #[arg(long, default_value_t = 8, help_heading = "Timing")]
+        concurrency: usize,
+
         /// Run the daemon in detached (background) mode.
         /// Writes a PID file and continues running independently.
         #[arg(short = 'd', long, help_heading = "Mode")]
@@ -27,52 +44,88 @@ pub enum DaemonAction {
         #[arg(long, help_heading = "Config")]
         config: Option<String>,
 
-        /// Number of ping cycles to run before exiting.
+        /// Number of wake-ups (ping cycles) to run before exiting.
         /// Useful for testing or one-off runs. If not provided, runs indefinitely.
         #[arg(long, help_heading = "Timing")]
         shots: Option<u64>,
 
-        /// Gas price, nonce, confirmation flags, etc.
-        /// These options control how transactions are submitted to the blockchain.
+        /// Gas limit and buffer for the ping transactions.
+        /// `--nonce` is ignored: the daemon assigns nonces per signer.
         #[command(flatten)]
         gas_confirm: GasAndConfirmArgs,
     },
 
-    /// Gracefully stop the running daemon (only valid if started in detached mode).
-    /// Reads the PID from `.zaphenathd.pid` and attempts to terminate the process.
-    Stop,
+    /// Gracefully stop the daemon pinging this config's keys, letting it record in-flight pings.
+    /// Reads its PID from the state directory, and cleans up a PID file whose process is gone.
+    Stop {
+        /// Optional override path to the config file the daemon was started with
+        /// (`daemon run --config`). If not provided, the default config path is used.
+        #[arg(long)]
+        config: Option<String>,
+    },
 
-    /// Show recent log output from the daemon.
+    /// Show recent log output from the daemon pinging this config's keys.
     /// Useful for debugging ping cycles and transaction results.
-    Logs,
+    Logs {
+        /// Optional override path to the config file the daemon was started with
+        /// (`daemon run --config`). If not provided, the default config path is used.
+        #[arg(long)]
+        config: Option<String>,
+    },
+
+    /// Show each key's ping health: its last successful ping and transaction,
+    /// and any failures the daemon is retrying.
+    Status {
+        /// Optional override path to the config file the daemon was started with
+        /// (`daemon run --config`). If not provided, the default config path is used.
+        #[arg(long)]
+        config: Option<String>,
+    },
 }
 
 pub async fn handle_daemon_command(action: &DaemonAction) {
     match action {
         DaemonAction::Run {
             interval,
+            ping_fraction,
+            concurrency,
             detached,
             config,
             gas_confirm,
             shots,
         } => {
             run::run_daemon(
-                *interval,
+                schedule::Schedule {
+                    fraction: *ping_fraction,
+                    max_sleep: *interval,
+                },
+                *concurrency,
                 *detached,
                 config.clone(),
                 gas_confirm.clone(),
                 shots,
-                gas_confirm.nonce,
             )
             .await
         }
 
-        DaemonAction::Stop => {
-            stop::stop_daemon();
+        DaemonAction::Stop { config } => {
+            stop::stop_daemon(&config_path(config));
         }
 
-        DaemonAction::Logs => {
-            logs::show_logs();
+        DaemonAction::Logs { config } => {
+            logs::show_logs(&config_path(config));
+        }
+
+        DaemonAction::Status { config } => {
+            status::show_status(&config_path(config));
         }
     }
 }
+
+/// The config named by a subcommand's `--config`, or the default one.
+fn config_path(config: &Option<String>) -> PathBuf {
+    config
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(get_config_path)
+}
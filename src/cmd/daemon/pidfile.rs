@@ -0,0 +1,150 @@
+use nix::{errno::Errno, sys::signal::kill, unistd::Pid};
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::Write,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+/// Whether a process with `pid` exists. A process we may not signal still counts.
+pub fn is_alive(pid: i32) -> bool {
+    matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM))
+}
+
+/// The PID recorded in `path`, if that process is still running.
+pub fn running_pid(path: &Path) -> Option<i32> {
+    let pid: i32 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    is_alive(pid).then_some(pid)
+}
+
+/// Held by the running daemon so a second one won't ping the same keys: an exclusive lock on
+/// the PID file, released when the process exits however it dies. Dropping it removes the
+/// PID file.
+#[derive(Debug)]
+pub struct PidLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl PidLock {
+    /// Locks `path` and writes our PID into it. A PID file left behind by a daemon that's no
+    /// longer running isn't locked, so it's simply taken over; a locked one is an error.
+    pub fn acquire(path: &Path) -> Result<Self, anyhow::Error> {
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => {
+                    let pid = fs::read_to_string(path).unwrap_or_default();
+                    return Err(anyhow::anyhow!(
+                        "❌ A daemon is already running (PID {}, see {})",
+                        pid.trim(),
+                        path.display()
+                    ));
+                }
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+            // A daemon exiting between our open and lock removed the file we locked
+            if !is_same_file(&file, path) {
+                continue;
+            }
+            file.set_len(0)?;
+            writeln!(file, "{}", std::process::id())?;
+            return Ok(PidLock {
+                path: path.to_path_buf(),
+                file,
+            });
+        }
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        // While we hold the lock nobody rewrites the file, but leave it be if the path now
+        // names another one
+        if is_same_file(&self.file, &self.path) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Removes the PID file at `path` unless a daemon holds it. Returns whether it was removed.
+pub fn remove_stale(path: &Path) -> Result<bool, anyhow::Error> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    match file.try_lock() {
+        Ok(()) if is_same_file(&file, path) => {
+            fs::remove_file(path)?;
+            Ok(true)
+        }
+        Ok(()) | Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// Whether `path` still names the open `file`.
+fn is_same_file(file: &File, path: &Path) -> bool {
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(open), Ok(named)) => open.dev() == named.dev() && open.ino() == named.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_is_exclusive_and_replaces_stale_pid_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("zaphenathd.pid");
+
+        let lock = PidLock::acquire(&path).unwrap();
+        assert_eq!(running_pid(&path), Some(std::process::id() as i32));
+        assert!(PidLock::acquire(&path).is_err());
+        drop(lock);
+        assert!(!path.exists());
+
+        // No process has this PID (it's above the kernel's limit)
+        fs::write(&path, "2147483647\n").unwrap();
+        assert_eq!(running_pid(&path), None);
+        let lock = PidLock::acquire(&path).unwrap();
+        assert_eq!(running_pid(&path), Some(std::process::id() as i32));
+        assert!(!remove_stale(&path).unwrap());
+        drop(lock);
+
+        fs::write(&path, "2147483647\n").unwrap();
+        assert!(remove_stale(&path).unwrap());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_only_one_of_two_racing_daemons_takes_over_a_stale_pid_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("zaphenathd.pid");
+
+        for _ in 0..50 {
+            fs::write(&path, "2147483647\n").unwrap();
+            let start = std::sync::Barrier::new(2);
+            let (first, second) = std::thread::scope(|scope| {
+                let race = || {
+                    start.wait();
+                    PidLock::acquire(&path)
+                };
+                let first = scope.spawn(race);
+                let second = scope.spawn(race);
+                (first.join().unwrap(), second.join().unwrap())
+            });
+
+            assert!(first.is_ok() != second.is_ok(), "{first:?} / {second:?}");
+            assert_eq!(running_pid(&path), Some(std::process::id() as i32));
+            drop((first, second));
+            assert!(!path.exists());
+        }
+    }
+}
@@ -1,149 +1,234 @@
+use super::{
+    pidfile::{self, PidLock},
+    schedule::Schedule,
+    state::DaemonFiles,
+};
 use crate::{
     cmd::types::GasAndConfirmArgs,
-    config::{get_config_path, read_config},
+    config::{KeyAction, get_config_path, read_config, update_config},
     contract::{
-        ping::ping_key_on_chain,
-        types::{ContractSpecs, GasSpecs, NetworkContext},
+        network::NetworkRegistry, nonce::NonceManager, ping::ping_key_on_chain, types::GasSpecs,
     },
 };
 use chrono::Utc;
-use serde_json::{Value, json};
 use std::{
     fs::{File, OpenOptions},
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, Stdio, exit},
-    thread,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Duration,
 };
+use tokio::{
+    signal::unix::{Signal, SignalKind, signal},
+    sync::Semaphore,
+    task::JoinSet,
+};
+use web3::types::H256;
+
+/// A signal the daemon acts on.
+enum Event {
+    /// SIGTERM or SIGINT: record pings already sent, skip the rest, then exit.
+    Shutdown,
+    /// SIGHUP: re-read the config now rather than when the next key is due.
+    Reload,
+}
+
+struct Signals {
+    terminate: Signal,
+    interrupt: Signal,
+    hangup: Signal,
+}
+
+impl Signals {
+    /// Takes over SIGTERM, SIGINT and SIGHUP, which no longer kill the process outright.
+    fn install() -> Result<Self, std::io::Error> {
+        Ok(Signals {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    async fn next(&mut self) -> Event {
+        tokio::select! {
+            _ = self.terminate.recv() => Event::Shutdown,
+            _ = self.interrupt.recv() => Event::Shutdown,
+            _ = self.hangup.recv() => Event::Reload,
+        }
+    }
+}
 
 /// Daemon entry point
 pub async fn run_daemon(
-    interval_secs: u64,
+    schedule: Schedule,
+    concurrency: usize,
     detached: bool,
     config_override: Option<String>,
     gas: GasAndConfirmArgs,
     shots: &Option<u64>,
-    nonce: Option<u64>,
 ) {
-    if detached {
-        detach_process(interval_secs, config_override, gas);
-        return;
-    }
-
     let config_path = config_override
+        .clone()
         .map(PathBuf::from)
         .unwrap_or_else(get_config_path);
+    let files = match DaemonFiles::for_config(&config_path) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{e}");
+            exit(1);
+        }
+    };
+
+    if detached {
+        detach_process(schedule, concurrency, config_override, gas, &files);
+        return;
+    }
 
-    let log_path = PathBuf::from(".zaphenathd.log");
     let mut log_file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&log_path)
+        .open(&files.log)
         .expect("Failed to open log file");
 
+    // Held until we return, so no second daemon pings the same keys meanwhile
+    let _lock = match PidLock::acquire(&files.pid) {
+        Ok(lock) => lock,
+        Err(e) => {
+            writeln!(log_file, "[{}] {}", Utc::now(), e).unwrap();
+            eprintln!("{e}");
+            exit(1);
+        }
+    };
+    let mut signals = Signals::install().expect("Failed to install signal handlers");
+
     writeln!(
         log_file,
-        "[{}] 🟢 Daemon started. Ping interval: {}s",
+        "[{}] 🟢 Daemon started. Pinging at {} of each timeout, sleeping at most {}s",
         Utc::now(),
-        interval_secs
+        schedule.fraction,
+        schedule.max_sleep
     )
     .unwrap();
+    if gas.nonce.is_some() {
+        writeln!(
+            log_file,
+            "[{}] ⚠️ --nonce is ignored: the daemon assigns nonces per signer",
+            Utc::now()
+        )
+        .unwrap();
+    }
+
+    // Shared by every ping, so keys signed by the same account get sequential nonces
+    let nonces = Arc::new(NonceManager::default());
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    // Set on shutdown, so pings still waiting for a permit are dropped rather than sent
+    let shutdown = Arc::new(AtomicBool::new(false));
 
     let mut current_shots = 0;
-    loop {
+    let mut stopping = false;
+    while !stopping {
         let now = Utc::now();
         writeln!(log_file, "[{}] 🔁 Starting ping cycle", now).unwrap();
 
         match read_config(&config_path) {
-            Ok(config_value) => {
-                if let Some(array) = config_value.as_array() {
-                    for key_entry in array {
-                        let key_id = match key_entry.get("key_id").and_then(Value::as_str) {
-                            Some(v) => v,
-                            None => {
-                                writeln!(log_file, "⚠️ Missing key_id field, skipping entry")
-                                    .unwrap();
-                                continue;
-                            }
-                        };
-
-                        let (contract_addr, priv_key_path, rpc_url, owner) = match (
-                            key_entry.get("contract_address").and_then(Value::as_str),
-                            key_entry.get("private_key_path").and_then(Value::as_str),
-                            key_entry.get("rpc_url").and_then(Value::as_str),
-                            key_entry.get("owner").and_then(Value::as_str),
-                        ) {
-                            (Some(c), Some(p), Some(r), Some(o)) => (c, p, r, o),
-                            _ => {
-                                writeln!(
-                                    log_file,
-                                    "⚠️ Incomplete key entry for {}, skipping",
-                                    key_id
-                                )
-                                .unwrap();
-                                continue;
-                            }
-                        };
-
-                        let network = key_entry
-                            .get("network")
-                            .and_then(Value::as_str)
-                            .map(str::to_string);
-
-                        let mut specs = ContractSpecs {
-                            ctx: NetworkContext {
-                                rpc_url: rpc_url.to_string(),
-                                network,
-                            },
-                            contract_addr: contract_addr.to_string(),
-                            priv_key_path: priv_key_path.to_string(),
-                            priv_key: None,
-                        };
+            Ok(config) => {
+                // Network names resolve through this config's registry, not the default one's
+                let registry = NetworkRegistry::new(config.networks.clone());
+                let mut pings = JoinSet::new();
+                for key_entry in config.keys {
+                    let key_id = key_entry.key_id.clone();
 
-                        let result = ping_key_on_chain(
-                            &mut specs,
-                            key_id,
-                            owner,
-                            true, // Automatically confirm
-                            GasSpecs {
-                                gas_limit: gas.gas_limit,
-                                gas_buffer: gas.gas_buffer,
-                            },
-                            nonce,
-                        )
-                        .await;
+                    // Someone else's key is only pinged on their behalf with `can_ping`
+                    if !key_entry.allows(KeyAction::Ping) {
+                        continue;
+                    }
+                    if schedule.next_due(&key_entry) > now.timestamp() {
+                        continue;
+                    }
 
-                        match result {
-                            Ok(tx_hash) => writeln!(
-                                log_file,
-                                "[{}] ✅ Pinged key {} (tx: {:?})",
-                                Utc::now(),
-                                key_id,
-                                tx_hash
-                            )
-                            .unwrap(),
-                            Err(e) => writeln!(
+                    let (mut specs, owner, key_hash) = match (
+                        key_entry.contract_specs_with(&registry),
+                        key_entry.owner(),
+                        key_entry.key_hash(),
+                    ) {
+                        (Ok(specs), Ok(owner), Ok(key_hash)) => {
+                            (specs, owner.to_string(), key_hash)
+                        }
+                        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                            writeln!(
                                 log_file,
-                                "[{}] ❌ Failed to ping key {}: {:?}",
-                                Utc::now(),
-                                key_id,
-                                e
+                                "⚠️ Incomplete key entry for {}, skipping: {}",
+                                key_id, e
                             )
-                            .unwrap(),
+                            .unwrap();
+                            continue;
                         }
+                    };
+                    specs.nonces = Some(nonces.clone());
 
-                        if let Ok(mut config) = read_config(&config_path) {
-                            if let Some(array) = config.as_array_mut() {
-                                if let Some(entry) =
-                                    array.iter_mut().find(|e| e["key_id"] == key_id)
-                                {
-                                    entry["last_ping_timestamp"] = json!(Utc::now().timestamp());
-                                }
-                            }
+                    let gas_specs = GasSpecs {
+                        gas_limit: gas.gas_limit,
+                        gas_buffer: gas.gas_buffer,
+                    };
+                    let (nonces, permits, shutdown) =
+                        (nonces.clone(), permits.clone(), shutdown.clone());
+                    pings.spawn(async move {
+                        let _permit = permits.acquire_owned().await;
+                        if shutdown.load(Ordering::SeqCst) {
+                            return (key_id, None);
+                        }
+                        let result = ping_key_on_chain(
+                            &mut specs, key_hash, &owner, true, // Automatically confirm
+                            gas_specs, None, // Nonces come from the manager
+                        )
+                        .await;
+                        // The transaction may have left a gap in the account's nonces
+                        if result.is_err()
+                            && let Ok(from) = specs.signer_address()
+                        {
+                            nonces.forget(from).await;
+                        }
+                        (key_id, Some(result))
+                    });
+                }
 
-                            // Persist updated config
-                            let _ = crate::config::write_config(&config_path, &config);
+                // Once asked to stop, pings still queued for a permit are skipped, while those
+                // already sent are waited for and recorded, so the config never misses a
+                // transaction that made it on-chain
+                loop {
+                    let joined = if stopping {
+                        pings.join_next().await
+                    } else {
+                        tokio::select! {
+                            joined = pings.join_next() => joined,
+                            event = signals.next() => {
+                                handle_event(&mut log_file, event, &mut stopping);
+                                shutdown.store(stopping, Ordering::SeqCst);
+                                continue;
+                            }
+                        }
+                    };
+                    let Some(joined) = joined else {
+                        break;
+                    };
+                    match joined {
+                        Ok((key_id, Some(result))) => {
+                            record_ping(&mut log_file, &config_path, &schedule, &key_id, result)
+                        }
+                        Ok((key_id, None)) => writeln!(
+                            log_file,
+                            "[{}] ⏭️ Skipped ping for key {}: shutting down",
+                            Utc::now(),
+                            key_id
+                        )
+                        .unwrap(),
+                        Err(e) => {
+                            writeln!(log_file, "[{}] ❌ Ping task failed: {:?}", Utc::now(), e)
+                                .unwrap()
                         }
                     }
                 }
@@ -160,24 +245,139 @@ pub async fn run_daemon(
         }
 
         current_shots += 1;
-        if let Some(some_shots) = shots {
-            if current_shots >= *some_shots {
-                break;
-            }
+        if stopping || shots.is_some_and(|some_shots| current_shots >= some_shots) {
+            break;
         }
 
+        // Sleep until the earliest key or retry is due, judged on what was just recorded
+        let sleep = match read_config(&config_path) {
+            Ok(config) => schedule.sleep_for(&config.keys, Utc::now().timestamp()),
+            Err(_) => Duration::from_secs(schedule.max_sleep),
+        };
+        writeln!(
+            log_file,
+            "[{}] 💤 Next ping due in {}s",
+            Utc::now(),
+            sleep.as_secs()
+        )
+        .unwrap();
         log_file.flush().unwrap();
-        thread::sleep(Duration::from_secs(interval_secs));
+        tokio::select! {
+            _ = tokio::time::sleep(sleep) => {}
+            event = signals.next() => handle_event(&mut log_file, event, &mut stopping),
+        }
+    }
+
+    writeln!(log_file, "[{}] 🔴 Daemon stopped", Utc::now()).unwrap();
+}
+
+/// Logs a signal received while pinging or sleeping.
+fn handle_event(log_file: &mut File, event: Event, stopping: &mut bool) {
+    match event {
+        Event::Shutdown => {
+            *stopping = true;
+            writeln!(
+                log_file,
+                "[{}] 🛑 Shutting down once the pings already sent are recorded",
+                Utc::now()
+            )
+            .unwrap();
+        }
+        Event::Reload => writeln!(log_file, "[{}] 🔄 Reloading config", Utc::now()).unwrap(),
+    }
+    log_file.flush().unwrap();
+}
+
+/// Logs a ping's outcome and records it in the config. Only a success moves the key's
+/// timestamp; a failure is retried with backoff.
+fn record_ping(
+    log_file: &mut File,
+    config_path: &Path,
+    schedule: &Schedule,
+    key_id: &str,
+    result: Result<H256, anyhow::Error>,
+) {
+    // Re-read the config under the lock so CLI commands running at the same time don't lose
+    // their changes
+    let at = Utc::now().timestamp();
+    let recorded = update_config(config_path, |config| {
+        let entry = config.find_key_mut(key_id)?;
+        Ok(match &result {
+            Ok(tx_hash) => {
+                entry.record_ping(at, Some(*tx_hash));
+                None
+            }
+            Err(e) => {
+                let failures = entry.consecutive_failures() + 1;
+                let retry_at = schedule.retry_at(entry, failures, at, rand::random());
+                entry.record_ping_failure(at, format!("{e:#}"), retry_at);
+                Some((failures, retry_at))
+            }
+        })
+    });
+
+    match (&result, &recorded) {
+        (Ok(tx_hash), _) => writeln!(
+            log_file,
+            "[{}] ✅ Pinged key {} (tx: {:?})",
+            Utc::now(),
+            key_id,
+            tx_hash
+        )
+        .unwrap(),
+        (Err(e), Ok(Some((failures, retry_at)))) => writeln!(
+            log_file,
+            "[{}] ❌ Failed to ping key {} ({} in a row), retrying in {}s: {:?}",
+            Utc::now(),
+            key_id,
+            failures,
+            retry_at - at,
+            e
+        )
+        .unwrap(),
+        (Err(e), _) => writeln!(
+            log_file,
+            "[{}] ❌ Failed to ping key {}: {:?}",
+            Utc::now(),
+            key_id,
+            e
+        )
+        .unwrap(),
+    }
+    if let Err(e) = &recorded {
+        writeln!(
+            log_file,
+            "[{}] ⚠️ Failed to record ping for key {}: {:?}",
+            Utc::now(),
+            key_id,
+            e
+        )
+        .unwrap();
     }
 }
 
 /// Forks the daemon to run in the background and writes PID
-fn detach_process(interval: u64, config_override: Option<String>, gas: GasAndConfirmArgs) {
+fn detach_process(
+    schedule: Schedule,
+    concurrency: usize,
+    config_override: Option<String>,
+    gas: GasAndConfirmArgs,
+    files: &DaemonFiles,
+) {
+    if let Some(pid) = pidfile::running_pid(&files.pid) {
+        eprintln!("❌ A daemon is already running (PID {pid})");
+        exit(1);
+    }
+
     let mut args = vec![
         "daemon".to_string(),
         "run".to_string(),
         "--interval".to_string(),
-        interval.to_string(),
+        schedule.max_sleep.to_string(),
+        "--ping-fraction".to_string(),
+        schedule.fraction.to_string(),
+        "--concurrency".to_string(),
+        concurrency.to_string(),
     ];
 
     if let Some(path) = config_override {
@@ -204,10 +404,8 @@ fn detach_process(interval: u64, config_override: Option<String>, gas: GasAndCon
         .spawn()
         .expect("Failed to fork daemon");
 
+    // The daemon writes its own PID file once it holds the lock
     let pid = child.id();
-    let mut pid_file = File::create(".zaphenathd.pid").expect("Failed to write PID file");
-    writeln!(pid_file, "{pid}").unwrap();
-
     println!("🚀 Daemon detached and running in background (PID: {pid})");
     exit(0);
 }
@@ -0,0 +1,182 @@
+use crate::config::{KeyAction, KeyEntry};
+use std::time::Duration;
+
+/// Delay before retrying a failed ping; it doubles with each further failure.
+const RETRY_BASE: u64 = 30;
+
+/// How the daemon spaces out each key's pings.
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    /// Share of a key's timeout to wait between pings, e.g. 0.5 pings twice per timeout.
+    pub fraction: f64,
+    /// The longest the daemon sleeps before re-reading the config, and the ping interval of
+    /// keys with neither a `ping_interval` nor a `timeout`.
+    pub max_sleep: u64,
+}
+
+impl Schedule {
+    /// Seconds between pings of `entry`: its own `ping_interval`, or a fraction of its timeout.
+    pub fn interval(&self, entry: &KeyEntry) -> u64 {
+        let interval = match (entry.ping_interval, entry.timeout) {
+            (Some(interval), _) => interval,
+            (None, Some(timeout)) => (timeout as f64 * self.fraction) as u64,
+            (None, None) => self.max_sleep,
+        };
+        interval.max(1)
+    }
+
+    /// When `entry` is next due, as a Unix timestamp: its pending retry after a failed ping,
+    /// or one interval after its last success. A key never pinged is due at once.
+    pub fn next_due(&self, entry: &KeyEntry) -> i64 {
+        if let Some(retry_at) = entry.health.as_ref().and_then(|h| h.retry_at) {
+            return retry_at;
+        }
+        match entry.last_ping_timestamp {
+            Some(last) => last.saturating_add(self.interval(entry) as i64),
+            None => i64::MIN,
+        }
+    }
+
+    /// When to retry `entry` after its `failures`th failed ping in a row at `now`. The delay
+    /// backs off exponentially, with `jitter` in [0, 1) spreading it over its upper half, and
+    /// never exceeds the key's interval. While the key's timeout hasn't run out, it is also kept
+    /// under half the time left, so several retries fit before the key expires.
+    pub fn retry_at(&self, entry: &KeyEntry, failures: u32, now: i64, jitter: f64) -> i64 {
+        let backoff = RETRY_BASE
+            .saturating_mul(1 << failures.saturating_sub(1).min(32))
+            .min(self.interval(entry));
+        let mut delay = backoff / 2 + (backoff as f64 / 2.0 * jitter) as u64;
+        if let (Some(last), Some(timeout)) = (entry.last_ping_timestamp, entry.timeout) {
+            let left = last.saturating_add(timeout as i64).saturating_sub(now);
+            if left > 0 {
+                delay = delay.min(left as u64 / 2);
+            }
+        }
+        now.saturating_add(delay.max(1) as i64)
+    }
+
+    /// How long to sleep from `now` until the earliest key we can ping is due. Entries
+    /// missing a signer or owner are left out, or they'd be due (and skipped) forever.
+    pub fn sleep_for(&self, keys: &[KeyEntry], now: i64) -> Duration {
+        let earliest = keys
+            .iter()
+            .filter(|k| k.allows(KeyAction::Ping) && k.key_source().is_ok() && k.owner().is_ok())
+            .map(|k| self.next_due(k))
+            .min();
+        let secs = match earliest {
+            Some(due) => due.saturating_sub(now).clamp(0, self.max_sleep as i64) as u64,
+            None => self.max_sleep,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+/// Parses `--ping-fraction`, which must lie in (0, 1].
+pub fn parse_fraction(s: &str) -> Result<f64, String> {
+    let fraction: f64 = s.parse().map_err(|_| format!("invalid fraction '{s}'"))?;
+    if fraction > 0.0 && fraction <= 1.0 {
+        Ok(fraction)
+    } else {
+        Err(format!("{s} is not in (0, 1]"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SignerEntry;
+
+    fn entry(timeout: Option<u64>, ping_interval: Option<u64>, last: Option<i64>) -> KeyEntry {
+        KeyEntry {
+            key_id: "k".to_string(),
+            key_hash: None,
+            contract_address: "0xdeadbeef".to_string(),
+            signer: SignerEntry {
+                private_key_path: Some("/fake/key".to_string()),
+                ..Default::default()
+            },
+            owner: Some("0x123abc".to_string()),
+            network: None,
+            rpc_url: None,
+            chain_id: None,
+            timeout,
+            ping_interval,
+            custodians: vec![],
+            last_ping_timestamp: last,
+            data: None,
+            role: None,
+            can_ping: false,
+            health: None,
+        }
+    }
+
+    const SCHEDULE: Schedule = Schedule {
+        fraction: 0.5,
+        max_sleep: 3600,
+    };
+
+    #[test]
+    fn test_interval_prefers_ping_interval_then_timeout_fraction() {
+        assert_eq!(SCHEDULE.interval(&entry(Some(600), Some(100), None)), 100);
+        assert_eq!(SCHEDULE.interval(&entry(Some(600), None, None)), 300);
+        assert_eq!(SCHEDULE.interval(&entry(None, None, None)), 3600);
+        assert_eq!(SCHEDULE.interval(&entry(Some(1), None, None)), 1);
+    }
+
+    #[test]
+    fn test_sleeps_until_earliest_due_key() {
+        let keys = [
+            entry(Some(31_536_000), None, Some(1_000)), // a year-long key, pinged at 1000
+            entry(Some(600), None, Some(1_000)),        // due at 1300
+        ];
+        assert_eq!(SCHEDULE.sleep_for(&keys, 1_100), Duration::from_secs(200));
+        // Overdue keys mean no sleep, far-off ones are capped so the config is re-read
+        assert_eq!(SCHEDULE.sleep_for(&keys, 2_000), Duration::ZERO);
+        assert_eq!(
+            SCHEDULE.sleep_for(&keys[..1], 1_100),
+            Duration::from_secs(3600)
+        );
+        // A key never pinged is due now, unless it can't be pinged at all
+        let mut new = entry(Some(600), None, None);
+        assert_eq!(SCHEDULE.sleep_for(&[new.clone()], 1_100), Duration::ZERO);
+        new.signer = SignerEntry::default();
+        assert_eq!(SCHEDULE.sleep_for(&[new], 1_100), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_keys_we_cannot_ping_are_not_scheduled() {
+        let mut theirs = entry(Some(60), None, None);
+        theirs.role = Some(crate::contract::types::Role::Reader);
+        assert_eq!(SCHEDULE.sleep_for(&[theirs], 0), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_retries_back_off_within_the_safety_window() {
+        // Pinged at 0 with a day-long timeout: plenty of time left
+        let key = entry(Some(86_400), None, Some(0));
+        assert_eq!(SCHEDULE.retry_at(&key, 1, 1_000, 0.0), 1_015);
+        assert_eq!(SCHEDULE.retry_at(&key, 1, 1_000, 0.99), 1_029);
+        assert_eq!(SCHEDULE.retry_at(&key, 3, 1_000, 0.0), 1_060);
+        // Capped at the key's interval, however many failures
+        assert_eq!(SCHEDULE.retry_at(&key, 40, 1_000, 0.0), 1_000 + 43_200 / 2);
+        // 100s before expiry, retries come within half of what's left
+        assert_eq!(SCHEDULE.retry_at(&key, 10, 86_300, 0.5), 86_350);
+
+        // A pending retry takes precedence over the regular interval
+        let mut failing = key.clone();
+        failing.record_ping_failure(1_000, "boom".to_string(), 1_015);
+        assert_eq!(SCHEDULE.next_due(&failing), 1_015);
+        assert_eq!(failing.consecutive_failures(), 1);
+        failing.record_ping(1_015, None);
+        assert_eq!(SCHEDULE.next_due(&failing), 1_015 + 43_200);
+        assert_eq!(failing.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_parse_fraction_bounds() {
+        assert_eq!(parse_fraction("0.25"), Ok(0.25));
+        assert!(parse_fraction("0").is_err());
+        assert!(parse_fraction("1.5").is_err());
+        assert!(parse_fraction("half").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Where the daemon keeps its PID and log files: `$XDG_STATE_HOME/zaphenath` (or the platform's
+//! equivalent), unless `ZAPHENATH_STATE_DIR` says otherwise. Each config file gets its own pair,
+//! so daemons pinging different configs can run side by side.
+
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the state directory.
+pub const STATE_DIR_ENV: &str = "ZAPHENATH_STATE_DIR";
+
+/// The daemon's files for one config.
+#[derive(Debug, Clone)]
+pub struct DaemonFiles {
+    pub pid: PathBuf,
+    pub log: PathBuf,
+}
+
+pub fn state_dir() -> PathBuf {
+    if let Ok(custom) = std::env::var(STATE_DIR_ENV) {
+        return PathBuf::from(custom);
+    }
+    // macOS and Windows have no state directory; local app data is the closest
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("zaphenath")
+}
+
+impl DaemonFiles {
+    /// The files of the daemon pinging the keys in `config_path`, creating the state directory
+    /// if needed.
+    pub fn for_config(config_path: &Path) -> Result<Self, anyhow::Error> {
+        let dir = state_dir();
+        fs::create_dir_all(&dir).map_err(|e| {
+            anyhow::anyhow!("❌ Could not create state directory {}: {e}", dir.display())
+        })?;
+        let name = format!("zaphenathd-{}", config_tag(config_path));
+        Ok(DaemonFiles {
+            pid: dir.join(format!("{name}.pid")),
+            log: dir.join(format!("{name}.log")),
+        })
+    }
+}
+
+/// A short name for a config file that's the same however its path is spelled: its file stem
+/// and a hash of its absolute path, e.g. `config-3f9a2b1c4d5e6f70`.
+fn config_tag(config_path: &Path) -> String {
+    let absolute = fs::canonicalize(config_path)
+        .or_else(|_| std::path::absolute(config_path))
+        .unwrap_or_else(|_| config_path.to_path_buf());
+    let digest = Sha256::digest(absolute.as_os_str().as_encoded_bytes());
+    let stem = absolute
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{stem}-{}", hex::encode(&digest[..8]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_tag_follows_the_file_not_the_spelling() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("other")).unwrap();
+        let config = dir.path().join("config.json");
+        fs::write(&config, "{}").unwrap();
+
+        let tag = config_tag(&config);
+        assert!(tag.starts_with("config-"));
+        assert_eq!(tag, config_tag(&dir.path().join("other/../config.json")));
+        assert_ne!(tag, config_tag(&dir.path().join("other/config.json")));
+    }
+}
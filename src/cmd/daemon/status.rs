@@ -0,0 +1,74 @@
+use super::{pidfile, state::DaemonFiles};
+use crate::config::{KeyAction, read_config};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+fn format_time(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Prints whether this config's daemon is running, then each pingable key's last successful
+/// ping and the daemon's recorded failures.
+pub fn show_status(config_path: &Path) {
+    let config = match read_config(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Failed to read config: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Ok(files) = DaemonFiles::for_config(config_path) {
+        match pidfile::running_pid(&files.pid) {
+            Some(pid) => println!("🟢 Daemon running (PID {pid})"),
+            None => println!("⚪ Daemon not running"),
+        }
+        println!("   Log: {}\n", files.log.display());
+    }
+
+    let keys: Vec<_> = config
+        .keys
+        .iter()
+        .filter(|k| k.allows(KeyAction::Ping))
+        .collect();
+    if keys.is_empty() {
+        println!("⚠️ No keys for the daemon to ping.");
+        return;
+    }
+
+    for key in keys {
+        let failures = key.consecutive_failures();
+        let mark = if failures == 0 { "✅" } else { "❌" };
+        println!("{mark} {}", key.key_id);
+        match key.last_ping_timestamp {
+            Some(at) => println!("   Last ping: {}", format_time(at)),
+            None => println!("   Last ping: never"),
+        }
+        if let Some(timeout) = key.timeout
+            && let Some(at) = key.last_ping_timestamp
+        {
+            println!(
+                "   Expires:   {}",
+                format_time(at.saturating_add(timeout as i64))
+            );
+        }
+        let Some(health) = &key.health else {
+            continue;
+        };
+        if let Some(tx) = &health.last_success_tx {
+            println!("   Last tx:   {tx}");
+        }
+        if failures > 0 {
+            println!("   Failures:  {failures} in a row");
+            if let Some(retry_at) = health.retry_at {
+                println!("   Retry at:  {}", format_time(retry_at));
+            }
+        }
+        if let Some(error) = &health.last_error {
+            let at = health.last_error_at.map(format_time).unwrap_or_default();
+            println!("   Last error ({at}): {error}");
+        }
+    }
+}
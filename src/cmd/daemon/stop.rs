@@ -1,13 +1,24 @@
+use super::{pidfile, state::DaemonFiles};
 #[cfg(unix)]
 use nix::sys::signal::{Signal, kill};
 #[cfg(unix)]
 use nix::unistd::Pid;
-use std::{fs, path::Path};
+use std::{fs, path::Path, thread, time::Duration};
 
-pub fn stop_daemon() {
-    let pid_path = ".zaphenathd.pid";
+/// How long `stop` waits for the daemon to record its in-flight pings and exit.
+const STOP_WAIT: Duration = Duration::from_secs(30);
 
-    if !Path::new(pid_path).exists() {
+pub fn stop_daemon(config_path: &Path) {
+    let files = match DaemonFiles::for_config(config_path) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let pid_path = files.pid.as_path();
+
+    if !pid_path.exists() {
         eprintln!("⚠️ PID file not found. Is the daemon running?");
         return;
     }
@@ -20,10 +31,21 @@ pub fn stop_daemon() {
         }
     };
 
+    // A daemon killed outright leaves its PID file behind. It's only removed if no daemon
+    // holds it, as one that's just starting may not have written its PID yet.
     let pid: u32 = match pid_str.parse() {
-        Ok(p) => p,
-        Err(_) => {
-            eprintln!("❌ Invalid PID in file: {pid_str}");
+        Ok(p) if pidfile::is_alive(p as i32) => p,
+        stale => {
+            match pidfile::remove_stale(pid_path) {
+                Ok(true) => match stale {
+                    Ok(p) => {
+                        println!("🧹 Daemon not running: removed the stale PID file of process {p}")
+                    }
+                    Err(_) => eprintln!("⚠️ Removed a PID file holding an invalid PID: {pid_str}"),
+                },
+                Ok(false) => eprintln!("⚠️ A daemon is starting up, try again in a moment"),
+                Err(e) => eprintln!("❌ Failed to remove the stale PID file: {e}"),
+            }
             return;
         }
     };
@@ -32,8 +54,22 @@ pub fn stop_daemon() {
     {
         match kill(Pid::from_raw(pid as i32), Signal::SIGTERM) {
             Ok(_) => {
-                println!("🛑 Sent SIGTERM to process {pid}");
-                let _ = fs::remove_file(pid_path);
+                // The daemon removes its PID file itself once its in-flight pings are recorded
+                println!("🛑 Sent SIGTERM to process {pid}, waiting for it to finish...");
+                let running = || pid_path.exists() && pidfile::is_alive(pid as i32);
+                let step = Duration::from_millis(200);
+                let mut waited = Duration::ZERO;
+                while running() && waited < STOP_WAIT {
+                    thread::sleep(step);
+                    waited += step;
+                }
+                if running() {
+                    println!(
+                        "⏳ Still finishing in-flight pings; it will exit once they're recorded"
+                    );
+                } else {
+                    println!("✅ Daemon stopped");
+                }
             }
             Err(e) => {
                 eprintln!("❌ Failed to kill process {pid}: {e}");
@@ -1,4 +1,9 @@
+pub mod agent;
 pub mod config;
 pub mod contract;
 pub mod daemon;
+pub mod network;
+pub mod profile;
+pub mod recover;
 pub mod types;
+pub mod wallet;
@@ -0,0 +1,109 @@
+use crate::{
+    config::{NetworkEntry, get_config_path, read_config, update_config},
+    contract::network::{NetworkRegistry, builtin_networks},
+};
+use clap::Subcommand;
+
+/// Actions for managing the network registry.
+/// Built-in networks (mainnet, sepolia, holesky, anvil, localhost) can be extended,
+/// or overridden by adding a network with the same name.
+#[derive(Subcommand)]
+pub enum NetworkAction {
+    /// List all known networks, built-in and user-defined.
+    List,
+    /// Add a network to the config, or replace an existing user-defined one.
+    Add {
+        /// Name used with `--network` (e.g. "base-sepolia").
+        #[arg(long)]
+        name: String,
+
+        /// RPC URL for the network. Repeat to add fallbacks, tried in order.
+        /// `${ENV}` references are expanded when the URL is used, e.g.
+        /// "https://sepolia.infura.io/v3/${INFURA_API_KEY}".
+        #[arg(long = "rpc-url", required = true)]
+        rpc_urls: Vec<String>,
+
+        /// The chain ID the RPC endpoints are expected to report.
+        #[arg(long)]
+        chain_id: u64,
+
+        /// Block explorer URL template for transactions, using `{tx}` for the hash
+        /// (e.g. "https://sepolia.etherscan.io/tx/{tx}").
+        #[arg(long)]
+        explorer_url: Option<String>,
+    },
+    /// Remove a user-defined network from the config.
+    Remove {
+        /// Name of the network to remove.
+        #[arg(long)]
+        name: String,
+    },
+}
+
+pub async fn handle_network_command(action: NetworkAction) {
+    if let Err(e) = run_network_command(action) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn run_network_command(action: NetworkAction) -> Result<(), anyhow::Error> {
+    let path = get_config_path();
+    match action {
+        NetworkAction::List => {
+            let config = read_config(&path)?;
+            let registry = NetworkRegistry::new(config.networks.clone());
+            for network in registry.networks() {
+                let source = if config.networks.iter().any(|n| n.name == network.name) {
+                    "user"
+                } else {
+                    "built-in"
+                };
+                println!("{} (chain {}, {source})", network.name, network.chain_id);
+                for url in &network.rpc_urls {
+                    println!("    rpc: {url}");
+                }
+                if let Some(explorer) = &network.explorer_url {
+                    println!("    explorer: {explorer}");
+                }
+            }
+        }
+        NetworkAction::Add {
+            name,
+            rpc_urls,
+            chain_id,
+            explorer_url,
+        } => {
+            let entry = NetworkEntry {
+                name: name.clone(),
+                rpc_urls,
+                chain_id,
+                explorer_url,
+            };
+            update_config(&path, |config| {
+                config.networks.retain(|n| n.name != entry.name);
+                config.networks.push(entry);
+                Ok(())
+            })?;
+            println!("✅ Network '{name}' saved.");
+        }
+        NetworkAction::Remove { name } => {
+            update_config(&path, |config| {
+                let before = config.networks.len();
+                config.networks.retain(|n| n.name != name);
+                if config.networks.len() == before {
+                    if builtin_networks().iter().any(|n| n.name == name) {
+                        return Err(anyhow::anyhow!(
+                            "❌ '{}' is a built-in network and cannot be removed. Add a network with the same name to override it.",
+                            name
+                        ));
+                    }
+                    return Err(anyhow::anyhow!("❌ Network '{}' not found in config", name));
+                }
+                Ok(())
+            })?;
+            println!("🗑️ Network '{name}' removed.");
+        }
+    }
+    Ok(())
+}
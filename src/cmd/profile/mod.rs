@@ -0,0 +1,140 @@
+use crate::{
+    cmd::types::SignerArgs,
+    config::{Profile, get_config_path, read_config, update_config},
+};
+use clap::Subcommand;
+
+/// Actions for managing named profiles in the local configuration file.
+/// A profile bundles a signer, a network and a contract address so they don't
+/// have to be repeated on every command.
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
+pub enum ProfileAction {
+    /// List all profiles, marking the default one.
+    List,
+    /// Add a profile, or replace an existing profile with the same name.
+    Add {
+        /// Name of the profile (e.g. "personal-sepolia", "team-anvil").
+        #[arg(long)]
+        name: String,
+
+        /// The signer used to sign transactions.
+        #[command(flatten)]
+        signer: SignerArgs,
+
+        /// The blockchain network to use (e.g., "mainnet", "sepolia", "anvil").
+        #[arg(long)]
+        network: Option<String>,
+
+        /// The RPC URL to use. Overrides the default URL of `--network`.
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// The address of the deployed Zaphenath smart contract.
+        #[arg(long)]
+        contract_address: Option<String>,
+
+        /// Also make this the default profile.
+        #[arg(long)]
+        default: bool,
+    },
+    /// Remove a profile from the config.
+    Remove {
+        /// Name of the profile to remove.
+        #[arg(long)]
+        name: String,
+    },
+    /// Make a profile the default for commands run without `--profile`.
+    Use {
+        /// Name of the profile to use by default.
+        #[arg(long)]
+        name: String,
+    },
+}
+
+pub async fn handle_profile_command(action: ProfileAction) {
+    if let Err(e) = run_profile_command(action) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn run_profile_command(action: ProfileAction) -> Result<(), anyhow::Error> {
+    let path = get_config_path();
+    match action {
+        ProfileAction::List => {
+            let config = read_config(&path)?;
+            if config.profiles.is_empty() {
+                println!("No profiles configured. Add one with `zaph profile add`.");
+            }
+            for (name, profile) in &config.profiles {
+                let marker = if config.default_profile.as_deref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                // Any kind of signer, as `wallet list` shows it
+                let signer = match profile.signer.key_source() {
+                    Ok(Some(source)) => source.to_string(),
+                    Ok(None) => "-".to_string(),
+                    Err(e) => format!("invalid ({e})"),
+                };
+                println!(
+                    "{marker} {name}: network={} rpc_url={} contract={} signer={}",
+                    profile.network.as_deref().unwrap_or("-"),
+                    profile.rpc_url.as_deref().unwrap_or("-"),
+                    profile.contract_address.as_deref().unwrap_or("-"),
+                    signer,
+                );
+            }
+        }
+        ProfileAction::Add {
+            name,
+            signer,
+            network,
+            rpc_url,
+            contract_address,
+            default,
+        } => {
+            update_config(&path, |config| {
+                config.profiles.insert(
+                    name.clone(),
+                    Profile {
+                        signer: signer.entry(),
+                        network,
+                        rpc_url,
+                        contract_address,
+                    },
+                );
+                if default {
+                    config.default_profile = Some(name.clone());
+                }
+                Ok(())
+            })?;
+            println!("✅ Profile '{name}' saved.");
+        }
+        ProfileAction::Remove { name } => {
+            update_config(&path, |config| {
+                if config.profiles.remove(&name).is_none() {
+                    return Err(anyhow::anyhow!("❌ Profile '{}' not found in config", name));
+                }
+                if config.default_profile.as_deref() == Some(name.as_str()) {
+                    config.default_profile = None;
+                }
+                Ok(())
+            })?;
+            println!("🗑️ Profile '{name}' removed.");
+        }
+        ProfileAction::Use { name } => {
+            update_config(&path, |config| {
+                if !config.profiles.contains_key(&name) {
+                    return Err(anyhow::anyhow!("❌ Profile '{}' not found in config", name));
+                }
+                config.default_profile = Some(name.clone());
+                Ok(())
+            })?;
+            println!("✅ '{name}' is now the default profile.");
+        }
+    }
+    Ok(())
+}
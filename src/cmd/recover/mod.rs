@@ -0,0 +1,50 @@
+use crate::{cmd::types::OutputArgs, contract::shamir};
+use clap::Subcommand;
+use std::process::exit;
+
+/// Recovery of secrets stored with `create-key --threshold`.
+#[derive(Subcommand)]
+pub enum RecoverAction {
+    /// Combine decrypted shares back into the secret.
+    /// Each custodian gets their share with `zaph contract read-key --decrypt`.
+    Combine {
+        /// The decrypted shares, at least as many as the threshold: each in hex, or a file
+        /// written by `read-key --decrypt --out`.
+        #[arg(required = true)]
+        shares: Vec<String>,
+
+        /// How to output the secret. Packed payloads are unpacked and verified first.
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+pub async fn handle_recover_command(action: RecoverAction) {
+    if let Err(e) = run_recover_command(action) {
+        eprintln!("{e}");
+        exit(1);
+    }
+}
+
+fn run_recover_command(action: RecoverAction) -> Result<(), anyhow::Error> {
+    match action {
+        RecoverAction::Combine { shares, output } => {
+            let shares = shares
+                .iter()
+                .map(|share| read_share(share))
+                .collect::<Result<Vec<_>, _>>()?;
+            output.emit(&shamir::combine(&shares)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// A share given on the command line, as hex or as the path of a file holding it.
+fn read_share(share: &str) -> Result<Vec<u8>, anyhow::Error> {
+    if std::path::Path::new(share).is_file() {
+        return std::fs::read(share)
+            .map_err(|e| anyhow::anyhow!("❌ Failed to read share file {share}: {e}"));
+    }
+    hex::decode(share.trim().trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("❌ Invalid hex in share (and no such file): {share}"))
+}
@@ -1,3 +1,18 @@
+use crate::{
+    config::{
+        KeyEntry, Profile, SignerEntry, get_config_path, load_active_profile, read_config,
+        update_config,
+    },
+    contract::{
+        ecies, key_id, network, padding, passphrase,
+        payload::{self, Compression, Payload},
+        shamir,
+        types::{ContractSpecs, KeySource, NetworkContext, Role},
+    },
+};
+use std::str::FromStr;
+use web3::types::{Address, H256};
+
 /// Arguments for controlling transaction confirmation and gas parameters.
 /// These arguments are commonly used for any command that sends an on-chain transaction.
 #[derive(clap::Args, Clone)]
@@ -24,12 +39,444 @@ pub struct GasAndConfirmArgs {
     pub nonce: Option<u64>,
 }
 
+/// The data stored under a key: either hex the user already encrypted, or a file packed
+/// with its metadata and, usually, encrypted here into a multi-recipient envelope.
+#[derive(clap::Args, Clone)]
+pub struct PayloadArgs {
+    /// The encrypted data (hex-encoded bytes) to associate with the key.
+    /// This data becomes readable by custodians after the timeout.
+    #[arg(
+        long,
+        required_unless_present = "plaintext_file",
+        conflicts_with = "plaintext_file"
+    )]
+    pub data: Option<String>,
+
+    /// File (or `-` for stdin) to store instead of passing `--data`. It's packed with its
+    /// content type, name, length and SHA-256, then encrypted for `--encrypt-for`.
+    #[arg(long)]
+    pub plaintext_file: Option<String>,
+
+    /// MIME type recorded for `--plaintext-file`. Guessed from the file extension by default.
+    #[arg(long, requires = "plaintext_file")]
+    pub content_type: Option<String>,
+
+    /// Gzip-compress `--plaintext-file` before encrypting it.
+    #[arg(long, requires = "plaintext_file")]
+    pub compress: bool,
+
+    /// Public key (hex, compressed or uncompressed) of a recipient who can decrypt the data.
+    /// Repeat or comma-separate for several; include your own to read it back yourself.
+    /// `zaph wallet address` prints the public key of a key file.
+    #[arg(long, value_delimiter = ',', requires = "plaintext_file")]
+    pub encrypt_for: Vec<String>,
+
+    /// Split the plaintext into Shamir shares, one per `--encrypt-for` recipient, so that
+    /// any THRESHOLD of them rebuild it with `zaph recover combine` but fewer learn nothing.
+    #[arg(long, requires = "encrypt_for")]
+    pub threshold: Option<u8>,
+
+    /// Encrypt `--plaintext-file` with a passphrase (Argon2id + ChaCha20-Poly1305) instead of
+    /// for public keys, for custodians who don't have an Ethereum key pair.
+    #[arg(long, requires = "plaintext_file", conflicts_with = "encrypt_for")]
+    pub encrypt_with_passphrase: bool,
+
+    /// Read that passphrase from the first line of this file instead of prompting twice.
+    #[arg(long, requires = "encrypt_with_passphrase")]
+    pub passphrase_file: Option<String>,
+
+    /// Pad the stored data up to the smallest of these sizes it fits in (e.g. `256,1k,4k`),
+    /// so its length on-chain doesn't reveal what it is. Bare `--pad` uses 256 B, 1 KiB and
+    /// 4 KiB; larger data is padded to a multiple of the largest size.
+    #[arg(long, value_delimiter = ',', num_args = 0.., value_parser = padding::parse_size)]
+    pub pad: Option<Vec<usize>>,
+}
+
+impl PayloadArgs {
+    /// The hex data to store on-chain: `--data` or the packed and encrypted `--plaintext-file`,
+    /// padded if `--pad` was given.
+    pub fn data_hex(&self) -> Result<String, anyhow::Error> {
+        let data = self.unpadded_hex()?;
+        let Some(buckets) = &self.pad else {
+            return Ok(data);
+        };
+        let data = hex::decode(data.trim_start_matches("0x"))
+            .map_err(|_| anyhow::anyhow!("Invalid hex data in --data: {data}"))?;
+        let buckets = if buckets.is_empty() {
+            padding::DEFAULT_BUCKETS.to_vec()
+        } else {
+            buckets.clone()
+        };
+        Ok(hex::encode(padding::pad(&data, &buckets)?))
+    }
+
+    fn unpadded_hex(&self) -> Result<String, anyhow::Error> {
+        let Some(path) = &self.plaintext_file else {
+            return Ok(self.data.clone().unwrap_or_default());
+        };
+        let compression = if self.compress {
+            Compression::Gzip
+        } else {
+            Compression::None
+        };
+        let plaintext = payload::pack(&Payload::from_file(
+            path,
+            self.content_type.as_deref(),
+            compression,
+        )?)?;
+        if self.encrypt_with_passphrase {
+            let passphrase = passphrase::read_passphrase(
+                self.passphrase_file.as_deref(),
+                "🔐 Passphrase to encrypt the payload with",
+                true,
+            )?;
+            let sealed = passphrase::seal(&plaintext, &passphrase, Default::default())?;
+            return Ok(hex::encode(sealed));
+        }
+        if self.encrypt_for.is_empty() {
+            eprintln!(
+                "⚠️ Storing {path} unencrypted, anyone can read it on-chain. Add --encrypt-for or --encrypt-with-passphrase to encrypt it."
+            );
+            return Ok(hex::encode(plaintext));
+        }
+
+        let recipients = self
+            .encrypt_for
+            .iter()
+            .map(|key| ecies::parse_public_key(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = match self.threshold {
+            Some(threshold) => shamir::split(&plaintext, threshold, &recipients)?,
+            None => ecies::seal(&plaintext, &recipients)?,
+        };
+        Ok(hex::encode(data))
+    }
+}
+
+/// How data read back from a key is output.
+#[derive(clap::Args, Clone)]
+pub struct OutputArgs {
+    /// Attempt to decode the output bytes as a UTF-8 string for human readability.
+    /// If the data is not valid UTF-8, it will be printed as raw bytes.
+    #[arg(long)]
+    pub decode: bool,
+
+    /// Write the data's raw bytes to this file instead of printing them.
+    #[arg(long)]
+    pub out: Option<String>,
+}
+
+impl OutputArgs {
+    /// Outputs key data, unpacking it first if it's a packed payload. Legacy data is
+    /// output as-is.
+    pub fn emit(&self, data: &[u8]) -> Result<(), anyhow::Error> {
+        let (data, is_text) = if payload::is_packed(data) {
+            let payload = payload::unpack(data)?;
+            eprintln!(
+                "📦 {} ({}, {} bytes{}, SHA-256 verified)",
+                payload.filename.as_deref().unwrap_or("payload"),
+                payload.content_type,
+                payload.data.len(),
+                if payload.compression == Compression::Gzip {
+                    ", gzip"
+                } else {
+                    ""
+                }
+            );
+            let is_text = payload.is_text();
+            (payload.data, is_text)
+        } else {
+            (data.to_vec(), false)
+        };
+
+        if let Some(path) = &self.out {
+            std::fs::write(path, &data)
+                .map_err(|e| anyhow::anyhow!("❌ Failed to write {path}: {e}"))?;
+            eprintln!("✅ Data written to {path}");
+        } else if self.decode || is_text {
+            let text = std::str::from_utf8(&data).map_err(|e| {
+                anyhow::anyhow!(
+                    "⚠️ Data is not valid UTF-8. Use --decode only if the content is encoded as UTF-8: {e:?}"
+                )
+            })?;
+            println!("{text}");
+        } else {
+            println!("0x{}", hex::encode(&data));
+        }
+        Ok(())
+    }
+}
+
+/// Arguments naming the key a command acts on, by its ID or by its raw on-chain hash.
+#[derive(clap::Args, Clone)]
+#[group(required = true, multiple = false)]
+pub struct KeyArgs {
+    /// The key's ID, as stored in the config.
+    #[arg(long)]
+    pub key_id: Option<String>,
+
+    /// The key's raw bytes32 on-chain ID (0x...), e.g. for a key created by another client.
+    /// Config entries are matched by the hash they resolve to.
+    #[arg(long, value_parser = key_id::parse)]
+    pub key_hash: Option<H256>,
+}
+
+impl KeyArgs {
+    /// How to look the key up in the config.
+    pub fn name(&self) -> String {
+        key_name(self.key_id.as_deref(), self.key_hash)
+    }
+}
+
+/// The config name for a key given by `--key-id` and/or `--key-hash`: its ID when there is
+/// one, otherwise the hash itself.
+pub fn key_name(key_id: Option<&str>, key_hash: Option<H256>) -> String {
+    match (key_id, key_hash) {
+        (Some(id), _) => id.to_string(),
+        (None, Some(hash)) => format!("{:#x}", hash),
+        (None, None) => unreachable!("clap requires --key-id or --key-hash"),
+    }
+}
+
+/// Arguments describing a key on the command line, for keys with no config entry (e.g. a
+/// custodian's view of someone else's key). Without `--owner` the key comes from the config,
+/// and any of the other flags given override the entry's values.
+#[derive(clap::Args, Clone)]
+pub struct AdHocKeyArgs {
+    /// The key's owner. Describes the key with these flags instead of a config entry;
+    /// the contract, network and signer default to the active profile's.
+    #[arg(long)]
+    pub owner: Option<String>,
+
+    /// The address of the deployed Zaphenath smart contract.
+    #[arg(long)]
+    pub contract_address: Option<String>,
+
+    /// The account that signs (or, for reads, calls) on this key.
+    #[command(flatten)]
+    pub signer: SignerArgs,
+
+    /// The network the key lives on.
+    #[command(flatten)]
+    pub network_specs: NetworkArgs,
+
+    /// Record the ad-hoc key in the config once the command succeeds.
+    #[arg(long, requires = "owner")]
+    pub save: bool,
+
+    /// Our role (Writer, Reader or None) on the ad-hoc key when someone else owns it. It
+    /// limits what may be done with the key and is recorded as a watch-only entry by `--save`.
+    #[arg(long, requires = "owner", value_parser = Role::from_str)]
+    pub watch_role: Option<Role>,
+
+    /// We may ping the ad-hoc key on the owner's behalf.
+    #[arg(long, requires = "watch_role")]
+    pub watch_can_ping: bool,
+}
+
+/// What happens to the config once a command on a key succeeds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Persist {
+    /// The key's config entry is updated.
+    Config,
+    /// The ad-hoc key is added to the config, then updated.
+    Save,
+    /// The key is ad-hoc and the config is left alone.
+    Skip,
+}
+
+/// The key a command acts on, resolved from the config or from ad-hoc flags.
+pub struct TargetKey {
+    pub entry: KeyEntry,
+    pub persist: Persist,
+}
+
+impl AdHocKeyArgs {
+    pub fn resolve(&self, key: &KeyArgs) -> Result<TargetKey, anyhow::Error> {
+        let config = read_config(&get_config_path())
+            .map_err(|e| anyhow::anyhow!("❌ Failed to read config: {e}"))?;
+        let name = key.name();
+
+        let Some(owner) = &self.owner else {
+            let mut entry = config.find_key(&name)?.clone();
+            let registry = network::NetworkRegistry::new(config.networks.clone());
+            self.apply_overrides(&mut entry, &registry)?;
+            return Ok(TargetKey {
+                entry,
+                persist: Persist::Config,
+            });
+        };
+
+        let owner = Address::from_str(owner)
+            .map_err(|_| anyhow::anyhow!("❌ Invalid --owner address '{owner}'"))?;
+        if self.save && config.contains_key(&name) {
+            return Err(anyhow::anyhow!(
+                "❌ Key '{name}' already exists in config, drop --save or --owner"
+            ));
+        }
+
+        let profile = config.active_profile()?;
+        let ctx = self.network_specs.resolve(profile)?;
+        let contract_address = self
+            .contract_address
+            .clone()
+            .or_else(|| profile?.contract_address.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!("❌ Missing --contract-address (and no profile sets one)")
+            })?;
+        let signer = SignerEntry::from(&self.signer.resolve(profile)?);
+
+        Ok(TargetKey {
+            entry: KeyEntry {
+                key_id: name,
+                key_hash: key.key_hash.map(|hash| format!("{:#x}", hash)),
+                contract_address,
+                signer,
+                owner: Some(format!("{:#x}", owner)),
+                network: ctx.network,
+                rpc_url: Some(ctx.rpc_url),
+                chain_id: ctx.chain_id,
+                timeout: None,
+                ping_interval: None,
+                custodians: vec![],
+                last_ping_timestamp: None,
+                data: None,
+                role: self.watch_role,
+                can_ping: self.watch_can_ping,
+                health: None,
+            },
+            persist: if self.save {
+                Persist::Save
+            } else {
+                Persist::Skip
+            },
+        })
+    }
+
+    /// Replaces the entry's contract, signer and network with any given on the command line.
+    /// The entry keeps its pinned chain ID unless `--network` names a known chain, so an
+    /// `--rpc-url` on another chain is still caught by the chain check.
+    fn apply_overrides(
+        &self,
+        entry: &mut KeyEntry,
+        registry: &network::NetworkRegistry,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(address) = &self.contract_address {
+            entry.contract_address = address.clone();
+        }
+        if let Some(source) = self.signer.entry().key_source()? {
+            entry.signer = SignerEntry::from(&source);
+        }
+        if self.network_specs.network.is_some() || self.network_specs.rpc_url.is_some() {
+            entry.network = self.network_specs.network.clone();
+            entry.rpc_url = self.network_specs.rpc_url.clone();
+            if let Some(known) = entry.network.as_deref().and_then(|n| registry.get(n)) {
+                entry.chain_id = Some(known.chain_id);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TargetKey {
+    /// Applies `f` to the key's config entry under the config lock, adding the entry first for
+    /// `--save`. Returns whether the config was touched at all; an entry added by someone else
+    /// since `resolve` is left alone with a warning.
+    pub fn update_config(
+        &self,
+        f: impl FnOnce(&mut KeyEntry) -> Result<(), anyhow::Error>,
+    ) -> Result<bool, anyhow::Error> {
+        if self.persist == Persist::Skip {
+            return Ok(false);
+        }
+        update_config(&get_config_path(), |config| {
+            if self.persist == Persist::Save {
+                // The transaction has already gone through, so a clash only skips the save
+                if config.contains_key(&self.entry.key_id) {
+                    eprintln!(
+                        "⚠️ Key '{}' already exists in config. Skipping save.",
+                        self.entry.key_id
+                    );
+                    return Ok(false);
+                }
+                config.keys.push(self.entry.clone());
+            }
+            f(config.find_key_mut(&self.entry.key_id)?)?;
+            Ok(true)
+        })
+    }
+}
+
+/// Arguments selecting the key that signs transactions. At most one source may be given;
+/// when none is, the active profile's signer is used.
+#[derive(clap::Args, Clone)]
+pub struct SignerArgs {
+    /// Path to the signer's private key: a hex-encoded key file or an encrypted v3 keystore.
+    #[arg(long, conflicts_with_all = ["private_key_env", "hd_path", "signer_url", "signer_command"])]
+    pub private_key_path: Option<String>,
+
+    /// Name of an environment variable holding the signer's hex-encoded private key.
+    #[arg(long, conflicts_with_all = ["hd_path", "signer_url", "signer_command"])]
+    pub private_key_env: Option<String>,
+
+    /// BIP-32 derivation path (e.g. "m/44'/60'/0'/0/3") deriving the signer from the mnemonic
+    /// given with `--mnemonic-file`, or prompted for when no file is set.
+    #[arg(long, conflicts_with_all = ["signer_url", "signer_command"])]
+    pub hd_path: Option<String>,
+
+    /// External signer speaking Clef's `account_signTransaction`, as an http(s) URL or a
+    /// Unix socket path. zaph sends it unsigned transactions and never sees the key.
+    #[arg(long, requires = "signer_address", conflicts_with = "signer_command")]
+    pub signer_url: Option<String>,
+
+    /// The account the external signer signs with.
+    #[arg(long, requires = "signer_url")]
+    pub signer_address: Option<String>,
+
+    /// Program that signs on zaph's behalf. It is run as `<program> address`,
+    /// `<program> sign-transaction` (JSON on stdin) or `<program> sign-message` (hex on stdin)
+    /// and prints its answer as hex.
+    #[arg(long)]
+    pub signer_command: Option<String>,
+}
+
+impl SignerArgs {
+    /// The signer given on the command line, in the shape stored in the config.
+    pub fn entry(&self) -> SignerEntry {
+        SignerEntry {
+            private_key_path: self.private_key_path.clone(),
+            private_key_env: self.private_key_env.clone(),
+            hd_path: self.hd_path.clone(),
+            signer_url: self.signer_url.clone(),
+            signer_address: self.signer_address.clone(),
+            signer_command: self.signer_command.clone(),
+        }
+    }
+
+    /// Picks the key source from the flags, falling back to the active profile's signer.
+    pub fn resolve(&self, profile: Option<&Profile>) -> Result<KeySource, anyhow::Error> {
+        if let Some(source) = self.entry().key_source()? {
+            return Ok(source);
+        }
+        profile
+            .map(|p| p.signer.key_source())
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ Missing a signer, e.g. --private-key-path (and no profile sets one)"
+                )
+            })
+    }
+}
+
 /// Arguments for specifying network connection details.
 /// These arguments define which blockchain network and RPC endpoint the client should connect to.
+/// When neither is given, the active profile's network settings are used.
 #[derive(clap::Args, Clone)]
 pub struct NetworkArgs {
     /// Specify the target blockchain network (e.g., "mainnet", "sepolia", "anvil").
-    /// This can influence default RPC URLs and chain IDs.
+    /// Names are resolved through the network registry (see `zaph network list`).
     #[arg(long)]
     pub network: Option<String>,
 
@@ -38,3 +485,115 @@ pub struct NetworkArgs {
     #[arg(long)]
     pub rpc_url: Option<String>,
 }
+
+impl NetworkArgs {
+    /// Builds the network context from the command-line flags, falling back to the
+    /// active profile's network settings when neither `--network` nor `--rpc-url` is given.
+    pub fn resolve(&self, profile: Option<&Profile>) -> Result<NetworkContext, anyhow::Error> {
+        let (rpc_url, network) = match profile {
+            Some(p) if self.rpc_url.is_none() && self.network.is_none() => {
+                (p.rpc_url.as_deref(), p.network.as_deref())
+            }
+            _ => (self.rpc_url.as_deref(), self.network.as_deref()),
+        };
+
+        // Keep the registry's reason: an unknown name, an unset `${ENV}` in the RPC URL...
+        network::build_network_context(rpc_url, network)
+            .map_err(|e| anyhow::anyhow!("❌ Invalid network settings: {e}"))
+    }
+}
+
+/// Builds contract specs for commands that don't start from a config entry,
+/// filling in anything not given on the command line from the active profile.
+pub fn contract_specs_from_args(
+    contract_address: Option<&str>,
+    signer: &SignerArgs,
+    network_specs: &NetworkArgs,
+) -> Result<ContractSpecs, anyhow::Error> {
+    let (ctx, contract_addr, key_source) =
+        key_location_from_args(contract_address, signer, network_specs)?;
+
+    Ok(ContractSpecs {
+        ctx,
+        contract_addr,
+        key_source: key_source?,
+        signer: None,
+        nonces: None,
+    })
+}
+
+/// Like `contract_specs_from_args`, but leaves it to the caller whether a missing signer
+/// is an error.
+pub fn key_location_from_args(
+    contract_address: Option<&str>,
+    signer: &SignerArgs,
+    network_specs: &NetworkArgs,
+) -> Result<(NetworkContext, String, Result<KeySource, anyhow::Error>), anyhow::Error> {
+    let profile = load_active_profile()?;
+    let ctx = network_specs.resolve(profile.as_ref())?;
+
+    let contract_addr = contract_address
+        .map(str::to_string)
+        .or_else(|| profile.as_ref()?.contract_address.clone())
+        .ok_or_else(|| {
+            anyhow::anyhow!("❌ Missing --contract-address (and no profile sets one)")
+        })?;
+    let key_source = signer.resolve(profile.as_ref());
+
+    Ok((ctx, contract_addr, key_source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkEntry;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        key: AdHocKeyArgs,
+    }
+
+    fn overridden(args: &[&str]) -> KeyEntry {
+        let mut entry = KeyEntry {
+            key_id: "k".to_string(),
+            key_hash: None,
+            contract_address: "0x0000000000000000000000000000000000000001".to_string(),
+            signer: SignerEntry::default(),
+            owner: None,
+            network: None,
+            rpc_url: Some("http://localhost:8545".to_string()),
+            chain_id: Some(31337),
+            timeout: None,
+            ping_interval: None,
+            custodians: vec![],
+            last_ping_timestamp: None,
+            data: None,
+            role: None,
+            can_ping: false,
+            health: None,
+        };
+        let registry = network::NetworkRegistry::new(vec![NetworkEntry {
+            name: "devnet".to_string(),
+            rpc_urls: vec!["http://localhost:9545".to_string()],
+            chain_id: 1337,
+            explorer_url: None,
+        }]);
+        let cli = Cli::parse_from(std::iter::once("zaph").chain(args.iter().copied()));
+        cli.key.apply_overrides(&mut entry, &registry).unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_network_overrides_keep_the_pinned_chain_unless_the_network_names_one() {
+        // Another endpoint must still be on the key's chain
+        let entry = overridden(&["--rpc-url", "http://elsewhere:8545"]);
+        assert_eq!(entry.rpc_url.as_deref(), Some("http://elsewhere:8545"));
+        assert_eq!(entry.chain_id, Some(31337));
+
+        let entry = overridden(&["--network", "devnet"]);
+        assert_eq!(entry.network.as_deref(), Some("devnet"));
+        assert_eq!(entry.chain_id, Some(1337));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    config::{get_config_path, read_config},
+    contract::{
+        ecies, keystore,
+        signer::{LocalKeySigner, Signer, parse_hex_key},
+        types::KeySource,
+    },
+};
+use clap::Subcommand;
+use dialoguer::Password;
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    process::exit,
+};
+use web3::signing::SecretKey;
+
+/// Create, import and inspect the signing keys used with `--private-key-path`.
+#[derive(Subcommand)]
+pub enum WalletAction {
+    /// Generate a new secp256k1 key and print its address.
+    New {
+        /// File to write the key to. Existing files are never overwritten.
+        path: String,
+
+        /// Write an encrypted v3 keystore instead of a plain hex key file.
+        /// The passphrase comes from `--password-file`, `ZAPHENATH_KEYSTORE_PASSWORD` or a prompt.
+        #[arg(long)]
+        keystore: bool,
+    },
+    /// Show the address of a hex key file or keystore, checking its format and permissions.
+    Address {
+        /// Hex key file or keystore.
+        path: String,
+    },
+    /// Encrypt an existing raw private key into a new keystore.
+    /// Reads the key from a file, an environment variable, or a prompt.
+    Import {
+        /// Keystore file to create.
+        path: String,
+
+        /// Plain hex key file to import.
+        #[arg(long, conflicts_with = "private_key_env")]
+        private_key_path: Option<String>,
+
+        /// Environment variable holding the hex key to import.
+        #[arg(long)]
+        private_key_env: Option<String>,
+    },
+    /// List the signers referenced by the config's keys and profiles.
+    List,
+}
+
+pub async fn handle_wallet_command(action: WalletAction) {
+    if let Err(e) = run_wallet_command(action) {
+        eprintln!("{e}");
+        exit(1);
+    }
+}
+
+fn run_wallet_command(action: WalletAction) -> Result<(), anyhow::Error> {
+    match action {
+        WalletAction::New { path, keystore } => {
+            let key = generate_key();
+            if keystore {
+                let passphrase = keystore::new_passphrase(&path)?;
+                keystore::encrypt(&path, &key, &passphrase)?;
+            } else {
+                write_hex_key(&path, &key)?;
+            }
+            println!("✅ New key written to {path}");
+            print_identity(&key)?;
+        }
+        WalletAction::Address { path } => {
+            if let Some(warning) = permission_warning(&path)? {
+                eprintln!("{warning}");
+            }
+            let signer = LocalKeySigner::from_file(&path)?;
+            print_identity(signer.secret_key())?;
+        }
+        WalletAction::Import {
+            path,
+            private_key_path,
+            private_key_env,
+        } => {
+            let key = match (private_key_path, private_key_env) {
+                (Some(key_path), _) => {
+                    let contents = fs::read_to_string(&key_path)
+                        .map_err(|e| anyhow::anyhow!("Failed to read {key_path}: {e}"))?;
+                    if keystore::is_keystore(&contents) {
+                        return Err(anyhow::anyhow!("❌ {key_path} is already a keystore"));
+                    }
+                    parse_hex_key(&contents).map_err(|_| {
+                        anyhow::anyhow!("Invalid private key format from path: {key_path}")
+                    })?
+                }
+                (None, Some(var)) => *LocalKeySigner::from_env(&var)?.secret_key(),
+                (None, None) => {
+                    let hex = Password::new()
+                        .with_prompt("🔑 Private key (hex)")
+                        .interact()
+                        .map_err(|e| anyhow::anyhow!("Failed to read private key: {e}"))?;
+                    parse_hex_key(&hex)
+                        .map_err(|_| anyhow::anyhow!("Invalid private key format"))?
+                }
+            };
+            let passphrase = keystore::new_passphrase(&path)?;
+            keystore::encrypt(&path, &key, &passphrase)?;
+            println!("✅ Key imported into {path}");
+            print_identity(&key)?;
+        }
+        WalletAction::List => list_wallets()?,
+    }
+    Ok(())
+}
+
+/// Prints the address that signs with `key`, and the public key custodians pass to
+/// `--encrypt-for`.
+fn print_identity(key: &SecretKey) -> Result<(), anyhow::Error> {
+    println!("📬 Address: {:#x}", LocalKeySigner::new(*key).address());
+    let public_key = ecies::public_key(&key.secret_bytes())?;
+    println!("🔓 Public key: 0x{}", hex::encode(public_key.serialize()));
+    Ok(())
+}
+
+fn generate_key() -> SecretKey {
+    // Almost every 32-byte string is a valid key; retry on the rare one that isn't.
+    loop {
+        if let Ok(key) = SecretKey::from_slice(&rand::random::<[u8; 32]>()) {
+            return key;
+        }
+    }
+}
+
+/// Writes `key` as hex to a new file that only the owner can read.
+fn write_hex_key(path: &str, key: &SecretKey) -> Result<(), anyhow::Error> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("❌ Failed to create {path}: {e}"))?;
+    writeln!(file, "0x{}", hex::encode(key.secret_bytes()))?;
+    Ok(())
+}
+
+/// A warning if a key file can be read by anyone but its owner.
+fn permission_warning(path: &str) -> Result<Option<String>, anyhow::Error> {
+    let mode = fs::metadata(path)
+        .map_err(|e| anyhow::anyhow!("❌ Cannot read {path}: {e}"))?
+        .permissions()
+        .mode();
+    Ok((mode & 0o077 != 0).then(|| {
+        format!(
+            "⚠️ {path} is accessible by other users (mode {:o}), run `chmod 600 {path}`",
+            mode & 0o777
+        )
+    }))
+}
+
+/// Groups the config's keys and profiles by signer. Nothing is unlocked: addresses come
+/// from the owners recorded for keys.
+fn list_wallets() -> Result<(), anyhow::Error> {
+    let config = read_config(&get_config_path())?;
+
+    let mut wallets: BTreeMap<String, (KeySource, Option<String>, Vec<String>)> = BTreeMap::new();
+    let profiles = config.profiles.iter().filter_map(|(name, profile)| {
+        let source = profile.signer.key_source().ok().flatten()?;
+        Some((source, None, format!("profile '{name}'")))
+    });
+    let keys = config.keys.iter().filter_map(|entry| {
+        let source = entry.key_source().ok()?;
+        Some((
+            source,
+            entry.owner.clone(),
+            format!("key '{}'", entry.key_id),
+        ))
+    });
+    for (source, address, user) in keys.chain(profiles) {
+        let wallet = wallets
+            .entry(source.to_string())
+            .or_insert_with(|| (source, None, Vec::new()));
+        wallet.1 = wallet.1.take().or(address);
+        wallet.2.push(user);
+    }
+
+    if wallets.is_empty() {
+        println!("No signers configured. Create a key with `zaph wallet new`.");
+    }
+    for (name, (source, address, users)) in &wallets {
+        println!(
+            "🔑 {name}: address={} used by {}",
+            address.as_deref().unwrap_or("-"),
+            users.join(", ")
+        );
+        if let KeySource::File(path) = source {
+            match permission_warning(path) {
+                Ok(Some(warning)) => println!("   {warning}"),
+                Ok(None) => {}
+                Err(e) => println!("   {e}"),
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,13 +1,412 @@
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use web3::{
-    signing::{Key, SecretKeyRef},
-    types::Address,
+use web3::{Web3, transports::Http, types::H256};
+
+use crate::contract::{
+    key_id, network,
+    types::{ContractSpecs, KeyData, KeySource, NetworkContext, Role},
 };
 
-use crate::contract::types::{ContractSpecs, KeyData};
+/// The schema version written by this build of zaph.
+/// Bump it (and add a step to `migrate`) whenever the on-disk format changes.
+pub const CONFIG_SCHEMA_VERSION: u64 = 1;
+
+/// The whole configuration document as stored on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub schema_version: u64,
+    /// Profile used when `--profile` is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// User-defined networks. These extend (or override) the built-in registry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<NetworkEntry>,
+    #[serde(default)]
+    pub keys: Vec<KeyEntry>,
+}
+
+/// A network known to the registry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkEntry {
+    pub name: String,
+    /// RPC endpoints, tried in order. `${ENV}` references are expanded at use time,
+    /// so API keys never need to be written to the config.
+    pub rpc_urls: Vec<String>,
+    /// The chain ID the endpoints are expected to report.
+    pub chain_id: u64,
+    /// Block explorer URL template for transactions, with `{tx}` as the hash placeholder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+}
+
+/// A named bundle of signer, network and contract defaults (e.g. "personal-sepolia").
+/// Command-line flags always take precedence over profile values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+    #[serde(flatten)]
+    pub signer: SignerEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+}
+
+/// Where a key entry or profile gets its signer from.
+/// Only one source is expected to be set; if several are, they're tried in field order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SignerEntry {
+    /// Hex private key file or encrypted v3 keystore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_path: Option<String>,
+    /// Environment variable holding a hex private key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_env: Option<String>,
+    /// BIP-32 path (e.g. `m/44'/60'/0'/0/3`) deriving the signer from the mnemonic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hd_path: Option<String>,
+    /// External signer (HTTP URL or Unix socket path) asked to sign for `signer_address`,
+    /// so zaph never holds the key material itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_address: Option<String>,
+    /// Program run to sign, see `contract::signer::CommandSigner` for the protocol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_command: Option<String>,
+}
+
+impl SignerEntry {
+    /// The configured key source, or `None` if no signer is set.
+    pub fn key_source(&self) -> Result<Option<KeySource>, anyhow::Error> {
+        if let Some(path) = &self.private_key_path {
+            return Ok(Some(KeySource::File(path.clone())));
+        }
+        if let Some(var) = &self.private_key_env {
+            return Ok(Some(KeySource::Env(var.clone())));
+        }
+        if let Some(path) = &self.hd_path {
+            return Ok(Some(KeySource::HdPath(path.clone())));
+        }
+        if let Some(url) = &self.signer_url {
+            let address = self.signer_address.clone().ok_or_else(|| {
+                anyhow::anyhow!("❌ 'signer_url' is set without 'signer_address'")
+            })?;
+            return Ok(Some(KeySource::External {
+                url: url.clone(),
+                address,
+            }));
+        }
+        if let Some(program) = &self.signer_command {
+            return Ok(Some(KeySource::Command(program.clone())));
+        }
+        Ok(None)
+    }
+}
+
+impl From<&KeySource> for SignerEntry {
+    fn from(source: &KeySource) -> Self {
+        match source {
+            KeySource::File(path) => SignerEntry {
+                private_key_path: Some(path.clone()),
+                ..Default::default()
+            },
+            KeySource::Env(var) => SignerEntry {
+                private_key_env: Some(var.clone()),
+                ..Default::default()
+            },
+            KeySource::HdPath(path) => SignerEntry {
+                hd_path: Some(path.clone()),
+                ..Default::default()
+            },
+            KeySource::External { url, address } => SignerEntry {
+                signer_url: Some(url.clone()),
+                signer_address: Some(address.clone()),
+                ..Default::default()
+            },
+            KeySource::Command(program) => SignerEntry {
+                signer_command: Some(program.clone()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A key tracked in the local config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyEntry {
+    pub key_id: String,
+    /// The on-chain keyId, when it isn't `keccak256(key_id)` (e.g. a salted ID).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_hash: Option<String>,
+    pub contract_address: String,
+    #[serde(flatten)]
+    pub signer: SignerEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    /// Chain the key lives on; RPC endpoints reporting another chain are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds between the daemon's pings, overriding its share of `timeout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_interval: Option<u64>,
+    #[serde(default)]
+    pub custodians: Vec<CustodianEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ping_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Our role on a key someone else owns, making this a watch-only entry.
+    /// `None` for keys we own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// Whether we may ping a watch-only key on the owner's behalf.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub can_ping: bool,
+    /// How recent pings went, kept by the daemon and `contract ping-key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<PingHealth>,
+}
+
+/// The outcome of a key's recent pings. `last_ping_timestamp` only moves on success, so this
+/// is where failures are remembered.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PingHealth {
+    /// Failed pings since the last successful one.
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<i64>,
+    /// When the daemon retries after a failure, as a Unix timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_success_tx: Option<String>,
+}
+
+/// What a command does to a key, checked against our role before anything is sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyAction {
+    Read,
+    Ping,
+    Update,
+    Delete,
+    ManageCustodians,
+}
+
+impl fmt::Display for KeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyAction::Read => write!(f, "read"),
+            KeyAction::Ping => write!(f, "pinged"),
+            KeyAction::Update => write!(f, "updated"),
+            KeyAction::Delete => write!(f, "deleted"),
+            KeyAction::ManageCustodians => write!(f, "given custodians"),
+        }
+    }
+}
+
+/// A custodian assigned to a key, as last confirmed on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustodianEntry {
+    pub address: String,
+    pub role: Role,
+    pub can_ping: bool,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        ConfigFile {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            default_profile: None,
+            profiles: BTreeMap::new(),
+            networks: vec![],
+            keys: vec![],
+        }
+    }
+}
+
+impl ConfigFile {
+    pub fn find_key(&self, key_id: &str) -> Result<&KeyEntry, anyhow::Error> {
+        self.keys
+            .iter()
+            .find(|k| k.matches(key_id))
+            .ok_or_else(|| anyhow::anyhow!("❌ Key '{}' not found in config", key_id))
+    }
+
+    pub fn find_key_mut(&mut self, key_id: &str) -> Result<&mut KeyEntry, anyhow::Error> {
+        self.keys
+            .iter_mut()
+            .find(|k| k.matches(key_id))
+            .ok_or_else(|| anyhow::anyhow!("❌ Key '{}' not found in config", key_id))
+    }
+
+    pub fn contains_key(&self, key_id: &str) -> bool {
+        self.keys.iter().any(|k| k.matches(key_id))
+    }
+
+    /// Returns the profile selected with `--profile`, falling back to `default_profile`.
+    /// Fails if a profile was asked for by name but does not exist.
+    pub fn active_profile(&self) -> Result<Option<&Profile>, anyhow::Error> {
+        let name = match std::env::var("ZAPHENATH_PROFILE") {
+            Ok(n) => Some(n),
+            Err(_) => self.default_profile.clone(),
+        };
+
+        match name {
+            Some(n) => self
+                .profiles
+                .get(&n)
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("❌ Profile '{}' not found in config", n)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl KeyEntry {
+    /// The keyId this entry is known by on-chain.
+    pub fn key_hash(&self) -> Result<H256, anyhow::Error> {
+        match &self.key_hash {
+            Some(hash) => {
+                key_id::parse(hash).map_err(|e| anyhow::anyhow!("{e} for key '{}'", self.key_id))
+            }
+            None => Ok(key_id::plain(&self.key_id)),
+        }
+    }
+
+    /// Our role on this key: `Owner` unless the entry is watch-only.
+    pub fn local_role(&self) -> Role {
+        self.role.unwrap_or(Role::Owner)
+    }
+
+    /// Whether our role lets us do `action`; the contract has the final say.
+    pub fn allows(&self, action: KeyAction) -> bool {
+        match (self.local_role(), action) {
+            (Role::Owner, _) => true,
+            (_, KeyAction::Ping) => self.can_ping,
+            (Role::Writer, KeyAction::Read | KeyAction::Update) => true,
+            (Role::Reader, KeyAction::Read) => true,
+            _ => false,
+        }
+    }
+
+    /// Fails with an explanation when our role doesn't allow `action`.
+    pub fn ensure_allows(&self, action: KeyAction) -> Result<(), anyhow::Error> {
+        if self.allows(action) {
+            return Ok(());
+        }
+        let ping = if self.can_ping { ", can ping" } else { "" };
+        Err(anyhow::anyhow!(
+            "❌ Key '{}' is watch-only (role {:?}{ping}): it can't be {action} from here",
+            self.key_id,
+            self.local_role()
+        ))
+    }
+
+    /// Failed pings since the last successful one.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.health.as_ref().map_or(0, |h| h.consecutive_failures)
+    }
+
+    /// Records a successful ping at `at`, clearing any pending retry.
+    pub fn record_ping(&mut self, at: i64, tx_hash: Option<H256>) {
+        self.last_ping_timestamp = Some(at);
+        if let Some(health) = &mut self.health {
+            health.consecutive_failures = 0;
+            health.retry_at = None;
+        }
+        if let Some(tx_hash) = tx_hash {
+            self.health
+                .get_or_insert_with(Default::default)
+                .last_success_tx = Some(format!("{tx_hash:#x}"));
+        }
+    }
+
+    /// Records a failed ping at `at`, leaving `last_ping_timestamp` alone, to be retried at
+    /// `retry_at`.
+    pub fn record_ping_failure(&mut self, at: i64, error: String, retry_at: i64) {
+        let health = self.health.get_or_insert_with(Default::default);
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+        health.last_error_at = Some(at);
+        health.retry_at = Some(retry_at);
+    }
+
+    /// Whether `name` refers to this entry, either by its ID or by its `0x` on-chain hash.
+    pub fn matches(&self, name: &str) -> bool {
+        self.key_id == name
+            || key_id::parse(name).is_ok_and(|hash| self.key_hash().is_ok_and(|own| own == hash))
+    }
+
+    pub fn owner(&self) -> Result<&str, anyhow::Error> {
+        self.owner
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("❌ Missing 'owner' for key '{}'", self.key_id))
+    }
+
+    pub fn key_source(&self) -> Result<KeySource, anyhow::Error> {
+        self.signer
+            .key_source()
+            .map_err(|e| anyhow::anyhow!("{e} for key '{}'", self.key_id))?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ No signer (private_key_path, private_key_env, hd_path, signer_url or signer_command) for key '{}'",
+                    self.key_id
+                )
+            })
+    }
+
+    /// Builds the contract specs needed to talk to the chain on behalf of this key, resolving
+    /// its network through the default config's registry.
+    pub fn contract_specs(&self) -> Result<ContractSpecs, anyhow::Error> {
+        let registry = network::NetworkRegistry::load().map_err(|e| anyhow::anyhow!("❌ {e}"))?;
+        self.contract_specs_with(&registry)
+    }
+
+    /// Like [`Self::contract_specs`], with networks resolved through `registry`, e.g. one built
+    /// from a config that isn't the default.
+    pub fn contract_specs_with(
+        &self,
+        registry: &network::NetworkRegistry,
+    ) -> Result<ContractSpecs, anyhow::Error> {
+        let mut ctx = network::build_network_context_with(
+            registry,
+            self.rpc_url.as_deref(),
+            self.network.as_deref(),
+        )
+        .map_err(|e| {
+            anyhow::anyhow!("❌ Invalid network settings for key '{}': {e}", self.key_id)
+        })?;
+        if self.chain_id.is_some() {
+            ctx.chain_id = self.chain_id;
+        }
+
+        Ok(ContractSpecs {
+            ctx,
+            contract_addr: self.contract_address.clone(),
+            key_source: self.key_source()?,
+            signer: None,
+            nonces: None,
+        })
+    }
+}
 
 /// Get the default configuration file path
 pub fn get_config_path() -> PathBuf {
@@ -32,10 +431,60 @@ pub fn get_config_path() -> PathBuf {
     path
 }
 
-/// Reads the configuration from disk
-pub fn read_config(path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+/// Upgrades a raw config document to `CONFIG_SCHEMA_VERSION`, one version at a time.
+///
+/// Version 0 is the original flat array of key entries written by older releases.
+pub fn migrate(mut value: Value) -> Result<Value, anyhow::Error> {
+    if value.is_array() {
+        value = json!({ "schema_version": 0, "keys": value });
+    }
+
+    loop {
+        let version = value
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| anyhow::anyhow!("Invalid config format: missing 'schema_version'"))?;
+
+        value = match version {
+            CONFIG_SCHEMA_VERSION => return Ok(value),
+            0 => migrate_v0_to_v1(value),
+            v if v > CONFIG_SCHEMA_VERSION => {
+                return Err(anyhow::anyhow!(
+                    "Config schema version {v} is newer than this build supports ({CONFIG_SCHEMA_VERSION}). Please upgrade zaph."
+                ));
+            }
+            v => return Err(anyhow::anyhow!("Unknown config schema version {v}")),
+        };
+    }
+}
+
+/// v0 -> v1: wrap the flat key array in a versioned document and normalise custodian roles.
+fn migrate_v0_to_v1(mut value: Value) -> Value {
+    if let Some(keys) = value["keys"].as_array_mut() {
+        for key in keys {
+            if let Some(custodians) = key["custodians"].as_array_mut() {
+                for custodian in custodians {
+                    if let Some(role) = custodian["role"].as_str() {
+                        custodian["role"] = json!(role.to_lowercase());
+                    }
+                }
+            }
+        }
+    }
+    value["schema_version"] = json!(1);
+    value
+}
+
+/// Loads the active profile (see `ConfigFile::active_profile`) from the default config path.
+pub fn load_active_profile() -> Result<Option<Profile>, anyhow::Error> {
+    let config = read_config(&get_config_path())?;
+    Ok(config.active_profile()?.cloned())
+}
+
+/// Reads the configuration from disk, migrating older formats on the fly
+pub fn read_config(path: &Path) -> Result<ConfigFile, anyhow::Error> {
     if !path.exists() {
-        return Ok(json!([]));
+        return Ok(ConfigFile::default());
     }
 
     let mut file = File::open(path)?;
@@ -43,22 +492,102 @@ pub fn read_config(path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
     file.read_to_string(&mut data)?;
 
     let value: Value = serde_json::from_str(&data)?;
-    Ok(value)
+    let config = serde_json::from_value(migrate(value)?)
+        .map_err(|e| anyhow::anyhow!("Invalid config format in {}: {e}", path.display()))?;
+    Ok(config)
 }
 
-/// Writes the configuration to disk
-pub fn write_config(path: &PathBuf, config: &Value) -> Result<(), std::io::Error> {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)?;
+/// Writes the configuration to disk atomically.
+///
+/// The document is written to a temporary file in the same directory, flushed, and then
+/// renamed over the real config, so readers only ever see the old or the new contents.
+/// Callers doing a read-modify-write should hold the `ConfigLock` (see `update_config`).
+pub fn write_config(path: &Path, config: &ConfigFile) -> Result<(), std::io::Error> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config.json".to_string());
+    let tmp_path = dir.join(format!(".{}.tmp.{}", file_name, std::process::id()));
 
     let data = serde_json::to_string_pretty(config).expect("Failed to serialize config");
-    file.write_all(data.as_bytes())?;
+
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Ok(d) = File::open(&dir) {
+        let _ = d.sync_all();
+    }
+
     Ok(())
 }
 
+/// An exclusive advisory lock on the config file, released on drop.
+///
+/// The lock lives on a sibling `<config>.lock` file so it survives the atomic renames
+/// done by `write_config`.
+pub struct ConfigLock {
+    _file: File,
+}
+
+/// Blocks until this process holds the exclusive lock for the config at `path`.
+pub fn lock_config(path: &Path) -> Result<ConfigLock, anyhow::Error> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+
+    if let Some(parent) = lock_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open lock file {}: {e}", lock_path.display()))?;
+    file.lock()
+        .map_err(|e| anyhow::anyhow!("Failed to lock {}: {e}", lock_path.display()))?;
+
+    Ok(ConfigLock { _file: file })
+}
+
+/// Re-reads the config, applies `f` and writes it back, all while holding the config lock.
+///
+/// Nothing is written if `f` returns an error.
+pub fn update_config<T>(
+    path: &Path,
+    f: impl FnOnce(&mut ConfigFile) -> Result<T, anyhow::Error>,
+) -> Result<T, anyhow::Error> {
+    let _lock = lock_config(path)?;
+    let mut config = read_config(path)?;
+    let out = f(&mut config)?;
+    write_config(path, &config)
+        .map_err(|e| anyhow::anyhow!("Failed to write config {}: {e}", path.display()))?;
+    Ok(out)
+}
+
 pub fn view_config(path: &Path) {
     // or path: &PathBuf
     match read_config(path) {
@@ -75,64 +604,107 @@ pub fn view_config(path: &Path) {
     }
 }
 
-/// Adds a new key entry to the configuration file
+/// Adds a new key entry to the configuration file. The signer is optional for watch-only
+/// entries, which must name their owner.
 pub async fn add_key(
-    path: &PathBuf,
-    contract_specs: &mut ContractSpecs,
+    path: &Path,
+    ctx: &NetworkContext,
+    contract_addr: &str,
+    key_source: Option<KeySource>,
     key_data: KeyData,
 ) -> Result<(), anyhow::Error> {
-    let mut config = match read_config(path) {
-        Ok(Value::Array(arr)) => arr,
-        Ok(_) => {
-            let e = anyhow::anyhow!("Invalid config format. Expected array of keys.");
-            return Err(e);
-        }
-        Err(_) => vec![],
+    let signer = key_source
+        .as_ref()
+        .map(SignerEntry::from)
+        .unwrap_or_default();
+    let mut contract_specs = key_source.map(|key_source| ContractSpecs {
+        ctx: ctx.clone(),
+        contract_addr: contract_addr.to_string(),
+        key_source,
+        signer: None,
+        nonces: None,
+    });
+    let mut load_signer = || {
+        contract_specs
+            .as_mut()
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ Missing a signer, e.g. --private-key-path (and no profile sets one)"
+                )
+            })?
+            .signer()
     };
 
     let owner = if let Some(own) = key_data.owner {
         own
     } else {
-        // 🧠 Derive owner address from private key
-        let sk = match contract_specs.get_private_key() {
-            Some(sec) => sec,
-            None => match contract_specs.load_private_key_if_missing() {
-                Ok(sec) => sec,
+        // 🧠 Derive owner address from the signer
+        let owner_addr = load_signer()
+            .map_err(|e| anyhow::anyhow!("Failed to load private key: {:?}", e))?
+            .address();
+        format!("{:#x}", owner_addr)
+    };
+
+    // A salted ID can only be derived again by the owner's own signer.
+    let key_hash = match key_data.scheme {
+        key_id::Scheme::Plain => None,
+        scheme => {
+            let hash = scheme
+                .key_hash(&key_data.id, || {
+                    let signer = load_signer()?;
+                    if format!("{:#x}", signer.address()) != owner.to_lowercase() {
+                        return Err(anyhow::anyhow!(
+                            "❌ A salted key ID must be derived by the owner's signer ({owner})"
+                        ));
+                    }
+                    Ok(signer)
+                })
+                .await?;
+            Some(format!("{:#x}", hash))
+        }
+    };
+
+    // Pin the key to a chain; ask the RPC endpoint when the network doesn't say.
+    let chain_id = match ctx.chain_id {
+        Some(id) => Some(id),
+        None => {
+            let web3 = Web3::new(Http::new(&ctx.rpc_url)?);
+            match network::verify_chain_id(&web3, ctx).await {
+                Ok(id) => Some(id),
                 Err(e) => {
-                    let e = anyhow::anyhow!("Failed to load private key: {:?}", e);
-                    return Err(e);
+                    eprintln!("⚠️ Could not determine chain ID, key will not be pinned: {e}");
+                    None
                 }
-            },
-        };
-        let owner_addr: Address = SecretKeyRef::new(sk).address();
-        let owner_addr_str = format!("{:#x}", owner_addr);
-        owner_addr_str
+            }
+        }
     };
 
-    let new_key = json!({
-        "key_id": key_data.id,
-        "contract_address": contract_specs.contract_addr,
-        "private_key_path": contract_specs.priv_key_path,
-        "owner": owner,
-        "rpc_url": contract_specs.ctx.rpc_url,
-        "network": contract_specs.ctx.network,
-        "timeout": key_data.timeout,
-        //"ping_interval": null,
-        "custodians": []
-    });
+    let new_key = KeyEntry {
+        key_id: key_data.id,
+        key_hash,
+        contract_address: contract_addr.to_string(),
+        signer,
+        owner: Some(owner),
+        network: ctx.network.clone(),
+        rpc_url: Some(ctx.rpc_url.clone()),
+        chain_id,
+        timeout: Some(key_data.timeout),
+        ping_interval: key_data.ping_interval,
+        custodians: vec![],
+        last_ping_timestamp: None,
+        data: None,
+        role: key_data.role,
+        can_ping: key_data.can_ping,
+        health: None,
+    };
 
-    config.push(new_key);
+    update_config(path, |config| {
+        config.keys.push(new_key);
+        Ok(())
+    })?;
 
-    match write_config(path, &json!(config)) {
-        Ok(_) => {
-            println!("Key successfully added to config.");
-            Ok(())
-        }
-        Err(err) => {
-            let e = anyhow::anyhow!("Failed to write config: {}", err);
-            Err(e)
-        }
-    }
+    println!("Key successfully added to config.");
+    Ok(())
 }
 
 #[cfg(test)]
@@ -142,27 +714,195 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn sample_entry(key_id: &str) -> KeyEntry {
+        KeyEntry {
+            key_id: key_id.to_string(),
+            key_hash: None,
+            contract_address: "0xdeadbeef".to_string(),
+            signer: SignerEntry {
+                private_key_path: Some("/fake/key".to_string()),
+                ..Default::default()
+            },
+            owner: Some("0x123abc".to_string()),
+            network: None,
+            rpc_url: Some("http://localhost:8545".to_string()),
+            chain_id: Some(31337),
+            timeout: Some(123),
+            ping_interval: None,
+            custodians: vec![],
+            last_ping_timestamp: None,
+            data: None,
+            role: None,
+            can_ping: false,
+            health: None,
+        }
+    }
+
+    #[test]
+    fn test_find_key_by_id_or_on_chain_hash() {
+        let mut salted = sample_entry("salted");
+        salted.key_hash = Some(format!("{:#x}", H256::repeat_byte(0xab)));
+        let config = ConfigFile {
+            keys: vec![sample_entry("plain"), salted],
+            ..Default::default()
+        };
+
+        let plain_hash = format!("{:#x}", key_id::plain("plain"));
+        assert_eq!(config.find_key(&plain_hash).unwrap().key_id, "plain");
+        assert_eq!(config.find_key(&"ab".repeat(32)).unwrap().key_id, "salted");
+        // A salted entry is no longer found by the hash of its name
+        assert!(!config.contains_key(&format!("{:#x}", key_id::plain("salted"))));
+    }
+
+    #[test]
+    fn test_contract_specs_resolve_networks_through_the_given_registry() {
+        let mut entry = sample_entry("k");
+        entry.rpc_url = None;
+        entry.chain_id = None;
+        entry.network = Some("devnet".to_string());
+        let registry = network::NetworkRegistry::new(vec![NetworkEntry {
+            name: "devnet".to_string(),
+            rpc_urls: vec!["http://localhost:9545".to_string()],
+            chain_id: 1337,
+            explorer_url: None,
+        }]);
+
+        let specs = entry.contract_specs_with(&registry).unwrap();
+        assert_eq!(specs.ctx.rpc_url, "http://localhost:9545");
+        assert_eq!(specs.ctx.chain_id, Some(1337));
+        assert!(
+            entry
+                .contract_specs_with(&network::NetworkRegistry::new(vec![]))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_write_and_read_config_roundtrip() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("config.json");
 
-        let sample = json!([
+        let sample = ConfigFile {
+            keys: vec![sample_entry("abc123")],
+            ..ConfigFile::default()
+        };
+
+        write_config(&file_path, &sample).expect("Failed to write config");
+
+        let result = read_config(&file_path).expect("Failed to read config");
+        assert_eq!(result.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(result.keys.len(), 1);
+        assert_eq!(result.keys[0].key_id, "abc123");
+        assert_eq!(result.keys[0].timeout, Some(123));
+    }
+
+    #[test]
+    fn test_read_config_migrates_legacy_array() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+
+        let legacy = json!([
             {
-                "key_id": "abc123",
-                "contract_address": "0xdeadbeef",
-                "private_key_path": "/fake/key",
-                "owner": "0x123abc",
-                "timeout": 123,
-                //"ping_interval": null,
-                "custodians": []
+                "key_id": "k1",
+                "contract_address": "0xabc",
+                "private_key_path": "/tmp/key",
+                "custodians": [
+                    { "address": "0xfeed", "role": "Reader", "can_ping": true }
+                ]
             }
         ]);
+        std::fs::write(&file_path, legacy.to_string()).unwrap();
 
-        write_config(&file_path, &sample).expect("Failed to write config");
+        let config = read_config(&file_path).expect("Failed to migrate legacy config");
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.keys[0].key_id, "k1");
+        assert_eq!(config.keys[0].owner, None);
+        assert_eq!(config.keys[0].custodians[0].role, Role::Reader);
+    }
 
-        let result = read_config(&file_path).expect("Failed to read config");
-        assert_eq!(result, sample);
+    #[test]
+    fn test_read_config_rejects_newer_schema() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+
+        let future = json!({ "schema_version": CONFIG_SCHEMA_VERSION + 1, "keys": [] });
+        std::fs::write(&file_path, future.to_string()).unwrap();
+
+        assert!(read_config(&file_path).is_err());
+    }
+
+    #[test]
+    fn test_active_profile_falls_back_to_default() {
+        let mut config = ConfigFile::default();
+        assert!(config.active_profile().unwrap().is_none());
+
+        config.profiles.insert(
+            "team-anvil".to_string(),
+            Profile {
+                network: Some("anvil".to_string()),
+                ..Profile::default()
+            },
+        );
+        config.default_profile = Some("team-anvil".to_string());
+
+        let profile = config.active_profile().unwrap().unwrap();
+        assert_eq!(profile.network.as_deref(), Some("anvil"));
+
+        config.default_profile = Some("missing".to_string());
+        assert!(config.active_profile().is_err());
+    }
+
+    #[test]
+    fn test_write_config_leaves_no_temp_files() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+
+        write_config(&file_path, &ConfigFile::default()).unwrap();
+        write_config(&file_path, &ConfigFile::default()).unwrap();
+
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["config.json".to_string()]);
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let path = file_path.clone();
+                std::thread::spawn(move || {
+                    update_config(&path, |config| {
+                        config.keys.push(sample_entry(&format!("key_{i}")));
+                        Ok(())
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let config = read_config(&file_path).unwrap();
+        assert_eq!(config.keys.len(), 8);
+    }
+
+    #[test]
+    fn test_update_config_does_not_write_on_error() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+
+        let result: Result<(), _> = update_config(&file_path, |config| {
+            config.keys.push(sample_entry("k1"));
+            Err(anyhow::anyhow!("boom"))
+        });
+        assert!(result.is_err());
+        assert!(!file_path.exists());
     }
 
     #[tokio::test] // gives a runtime and lets the fn be async
@@ -179,47 +919,73 @@ mod tests {
         // call the async function and bubble up any error with `?`
         add_key(
             &file_path,
-            &mut ContractSpecs {
-                ctx,
-                contract_addr: "key_1".to_string(),
-                priv_key_path: "/path/to/key".to_string(),
-                priv_key: None,
-            },
+            &ctx,
+            "key_1",
+            Some(KeySource::File("/path/to/key".to_string())),
             KeyData {
                 id: "key_1".to_string(),
                 owner: Some("0x123abc".to_string()),
                 timeout: 42,
+                ping_interval: None,
+                scheme: key_id::Scheme::Plain,
+                role: None,
+                can_ping: false,
             },
         )
         .await?; // <- await and propagate
 
         // now verify that the file was written correctly
         let config = read_config(&file_path).unwrap();
-        assert!(config.is_array());
-        let arr = config.as_array().unwrap();
-        assert_eq!(arr.len(), 1);
-        assert_eq!(arr[0]["key_id"], "key_1");
-        assert_eq!(arr[0]["timeout"], 42);
+        assert_eq!(config.keys.len(), 1);
+        assert_eq!(config.keys[0].key_id, "key_1");
+        assert_eq!(config.keys[0].timeout, Some(42));
+        assert_eq!(config.keys[0].chain_id, Some(1));
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_add_watch_only_key_without_signer() -> Result<(), anyhow::Error> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("config.json");
+        let ctx = network::build_network_context(None, Some("mainnet")).unwrap();
+
+        let key_data = |owner: Option<&str>| KeyData {
+            id: "theirs".to_string(),
+            owner: owner.map(str::to_string),
+            timeout: 42,
+            ping_interval: None,
+            scheme: key_id::Scheme::Plain,
+            role: Some(Role::Reader),
+            can_ping: true,
+        };
+        // Without a signer, the owner can't be derived
+        assert!(
+            add_key(&file_path, &ctx, "0xc0", None, key_data(None))
+                .await
+                .is_err()
+        );
+        add_key(&file_path, &ctx, "0xc0", None, key_data(Some("0x123abc"))).await?;
+
+        let entry = &read_config(&file_path)?.keys[0];
+        assert_eq!(entry.signer, SignerEntry::default());
+        assert_eq!(entry.role, Some(Role::Reader));
+        assert!(entry.allows(KeyAction::Read));
+        assert!(entry.allows(KeyAction::Ping));
+        assert!(!entry.allows(KeyAction::Update));
+        assert!(entry.ensure_allows(KeyAction::Delete).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_view_config_prints_json() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("config.json");
 
-        let config = json!([
-            {
-                "key_id": "k1",
-                "contract_address": "0xabc",
-                "private_key_path": "/tmp/key",
-                "owner": "0x123abc",
-                "timeout": 300,
-                //"ping_interval": null,
-                "custodians": []
-            }
-        ]);
+        let config = ConfigFile {
+            keys: vec![sample_entry("k1")],
+            ..ConfigFile::default()
+        };
         write_config(&file_path, &config).unwrap();
 
         // Redirect stdout to capture print
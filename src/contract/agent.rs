@@ -0,0 +1,222 @@
+use super::{
+    signer::{Signer, TransactionRequest},
+    types::KeySource,
+};
+use crate::config::SignerEntry;
+use futures::{FutureExt, future::BoxFuture};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    time::Duration,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use web3::types::{Address, Bytes};
+
+/// Environment variable naming the agent's socket. When set, signers are requested from the
+/// agent before falling back to loading (and unlocking) them locally.
+pub const AGENT_SOCK_ENV: &str = "ZAPH_AGENT_SOCK";
+
+/// How long to wait for the agent's answer, so a wedged agent fails the command instead of
+/// hanging it.
+const AGENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A request to the agent, sent as a single JSON line.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum AgentRequest {
+    /// Which account, if any, the agent holds for this signer.
+    Address {
+        signer: SignerEntry,
+    },
+    SignTransaction {
+        address: Address,
+        tx: TransactionRequest,
+    },
+    SignMessage {
+        address: Address,
+        message: Bytes,
+    },
+}
+
+/// The agent's answer, sent back as a single JSON line.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentResponse {
+    Address(Option<Address>),
+    Signed(Bytes),
+    Error(String),
+}
+
+/// How the agent and its clients name a signer: its config entry, with file paths made
+/// absolute so the agent and the CLI agree regardless of their working directories.
+pub fn signer_identity(source: &KeySource) -> SignerEntry {
+    let source = match source {
+        KeySource::File(path) => KeySource::File(
+            std::fs::canonicalize(path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| path.clone()),
+        ),
+        other => other.clone(),
+    };
+    SignerEntry::from(&source)
+}
+
+/// Sends one request to the agent listening on `socket` and waits for its answer.
+pub async fn request(socket: &str, request: &AgentRequest) -> Result<AgentResponse, anyhow::Error> {
+    let exchange = async {
+        let mut stream = tokio::net::UnixStream::connect(socket)
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ Could not reach the key agent at {socket}: {e}"))?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+
+        let mut response = String::new();
+        tokio::io::BufReader::new(stream)
+            .read_line(&mut response)
+            .await?;
+        Ok::<_, anyhow::Error>(response)
+    };
+    let response = tokio::time::timeout(AGENT_TIMEOUT, exchange)
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "❌ Key agent at {socket} didn't answer within {}s",
+                AGENT_TIMEOUT.as_secs()
+            )
+        })??;
+    parse_response(&response)
+}
+
+/// The blocking counterpart of `request` over an already connected `stream`, for loading
+/// signers outside async code.
+fn exchange(
+    mut stream: UnixStream,
+    request: &AgentRequest,
+) -> Result<AgentResponse, anyhow::Error> {
+    stream.set_read_timeout(Some(AGENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(AGENT_TIMEOUT))?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> Result<AgentResponse, anyhow::Error> {
+    serde_json::from_str(response)
+        .map_err(|e| anyhow::anyhow!("Invalid response from the key agent: {e}"))
+}
+
+/// A signer whose key lives in the agent; every signature is a round trip over the socket.
+#[derive(Debug)]
+pub struct AgentSigner {
+    socket: String,
+    address: Address,
+}
+
+impl AgentSigner {
+    /// Returns the agent's signer for `source`, or `None` if the agent doesn't hold it or
+    /// can't be reached. An agent that answers with an error is an error.
+    pub fn for_source(socket: &str, source: &KeySource) -> Result<Option<Self>, anyhow::Error> {
+        let stream = match UnixStream::connect(socket) {
+            Ok(stream) => stream,
+            // An agent that exited, e.g. after its idle timeout, leaves the variable behind
+            Err(e) => {
+                eprintln!("⚠️ Key agent at {socket} is unreachable ({e}), loading the key locally");
+                return Ok(None);
+            }
+        };
+        let signer = signer_identity(source);
+        match exchange(stream, &AgentRequest::Address { signer })? {
+            AgentResponse::Address(address) => Ok(address.map(|address| AgentSigner {
+                socket: socket.to_string(),
+                address,
+            })),
+            AgentResponse::Error(e) => Err(anyhow::anyhow!("Key agent error: {e}")),
+            AgentResponse::Signed(_) => Err(anyhow::anyhow!("Unexpected response from key agent")),
+        }
+    }
+
+    async fn sign(&self, request: AgentRequest) -> Result<Bytes, anyhow::Error> {
+        match self::request(&self.socket, &request).await? {
+            AgentResponse::Signed(bytes) => Ok(bytes),
+            AgentResponse::Error(e) => Err(anyhow::anyhow!("Key agent refused to sign: {e}")),
+            AgentResponse::Address(_) => Err(anyhow::anyhow!("Unexpected response from key agent")),
+        }
+    }
+}
+
+impl Signer for AgentSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        tx: &'a TransactionRequest,
+    ) -> BoxFuture<'a, Result<Bytes, anyhow::Error>> {
+        async move {
+            self.sign(AgentRequest::SignTransaction {
+                address: self.address,
+                tx: tx.clone(),
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn sign_message<'a>(
+        &'a self,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, anyhow::Error>> {
+        async move {
+            self.sign(AgentRequest::SignMessage {
+                address: self.address,
+                message: Bytes(message.to_vec()),
+            })
+            .await
+            .map(|bytes| bytes.0)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wedged_agent_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("agent.sock");
+        // Accepts connections but never answers
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let _wedged = tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            std::future::pending::<()>().await
+        });
+
+        let signer = AgentSigner {
+            socket: socket.to_string_lossy().into_owned(),
+            address: Address::zero(),
+        };
+        let err = signer.sign_message(b"hi").await.unwrap_err();
+        assert!(err.to_string().contains("didn't answer"), "{err}");
+    }
+
+    #[test]
+    fn test_unreachable_agent_holds_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("gone.sock");
+        let source = KeySource::Env("ZAPH_TEST_UNSET_KEY".to_string());
+        assert!(
+            AgentSigner::for_source(socket.to_str().unwrap(), &source)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
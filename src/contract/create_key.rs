@@ -1,14 +1,14 @@
 use crate::contract::types::{ContractSpecs, GasSpecs};
+use crate::contract::{network, transaction};
 use dialoguer::Confirm;
 use std::str::FromStr;
 use web3::contract::{Contract, Options};
-use web3::signing::{Key, SecretKeyRef};
 use web3::transports::Http;
 use web3::types::{Address, Bytes, H256, U64, U256};
 
 pub async fn create_key_on_chain(
     contract_specs: &mut ContractSpecs,
-    key_id: &str,
+    key_hash: H256,
     data: &str,
     timeout: u64,
     yes: bool,
@@ -17,23 +17,17 @@ pub async fn create_key_on_chain(
 ) -> Result<H256, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
     let web3 = web3::Web3::new(http);
+    let chain_id = network::verify_chain_id(&web3, &contract_specs.ctx).await?;
+    contract_specs.ctx.chain_id = Some(chain_id);
 
     let contract_address = Address::from_str(&contract_specs.contract_addr)
         .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
 
-    let sk = if let Some(sec) = contract_specs.get_private_key() {
-        sec
-    } else {
-        // here you know you need exclusive access
-        contract_specs.load_private_key_if_missing()? // requires &mut self
-    };
-    let wallet = SecretKeyRef::new(sk);
+    let from = contract_specs.signer_address()?;
 
     let abi_json = include_str!("../../abi/Zaphenath.json");
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_id.as_bytes()).as_slice());
-
     let data_bytes = Bytes::from(
         hex::decode(data).map_err(|_| anyhow::anyhow!("Invalid hex data in --data: {}", data))?,
     );
@@ -46,12 +40,7 @@ pub async fn create_key_on_chain(
         None
     } else {
         let est: U256 = contract
-            .estimate_gas(
-                "createKey",
-                call_params.clone(),
-                wallet.address(),
-                Options::default(),
-            )
+            .estimate_gas("createKey", call_params.clone(), from, Options::default())
             .await?;
 
         let gas_with_buffer = if let Some(buffer) = gas_specs.gas_buffer {
@@ -83,9 +72,15 @@ pub async fn create_key_on_chain(
         ..Options::default()
     };
 
-    let tx_hash = contract
-        .signed_call("createKey", call_params, options, wallet)
-        .await?;
+    let tx_hash = transaction::send_transaction(
+        &web3,
+        &contract,
+        contract_specs,
+        "createKey",
+        call_params,
+        options,
+    )
+    .await?;
 
     println!("⏳ Waiting for transaction {:?} to be mined...", tx_hash);
 
@@ -108,6 +103,9 @@ pub async fn create_key_on_chain(
     if let Some(status) = receipt.status {
         if status == U64::one() {
             println!("✅ Key created on-chain. Tx hash: {:?}", tx_hash);
+            if let Some(link) = contract_specs.ctx.explorer_link(&tx_hash) {
+                println!("🔗 {link}");
+            }
             Ok(tx_hash)
         } else {
             Err(anyhow::anyhow!(
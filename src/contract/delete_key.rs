@@ -1,15 +1,14 @@
 use crate::contract::types::{ContractSpecs, GasSpecs};
+use crate::contract::{network, transaction};
 use dialoguer::Confirm;
-use std::fs;
 use std::str::FromStr;
 use web3::contract::{Contract, Options};
-use web3::signing::{Key, SecretKeyRef};
 use web3::transports::Http;
 use web3::types::{Address, H256, U64, U256};
 
 pub async fn delete_key_on_chain(
-    contract_specs: &ContractSpecs,
-    key_id: &str,
+    contract_specs: &mut ContractSpecs,
+    key_hash: H256,
     owner_address: &str,
     yes: bool,
     gas_specs: GasSpecs,
@@ -17,23 +16,16 @@ pub async fn delete_key_on_chain(
 ) -> Result<H256, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
     let web3 = web3::Web3::new(http);
+    network::verify_chain_id(&web3, &contract_specs.ctx).await?;
 
     let contract_address = Address::from_str(&contract_specs.contract_addr)
         .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
 
-    let sk_bytes = fs::read_to_string(&contract_specs.priv_key_path)?
-        .trim()
-        .trim_start_matches("0x")
-        .to_string();
-
-    let sk = web3::signing::SecretKey::from_str(&sk_bytes)
-        .map_err(|_| anyhow::anyhow!("Invalid private key format"))?;
-    let wallet = SecretKeyRef::new(&sk);
+    let from = contract_specs.signer_address()?;
 
     let abi_json = include_str!("../../abi/Zaphenath.json");
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_id.as_bytes()).as_slice());
     let owner = Address::from_str(owner_address)?;
 
     let call_params = (key_hash, owner);
@@ -44,12 +36,7 @@ pub async fn delete_key_on_chain(
         None
     } else {
         let est: U256 = contract
-            .estimate_gas(
-                "deleteKey",
-                call_params,
-                wallet.address(),
-                Options::default(),
-            )
+            .estimate_gas("deleteKey", call_params, from, Options::default())
             .await?;
 
         let gas_with_buffer = if let Some(buffer) = gas_specs.gas_buffer {
@@ -81,9 +68,15 @@ pub async fn delete_key_on_chain(
         ..Options::default()
     };
 
-    let tx_hash = contract
-        .signed_call("deleteKey", call_params, options, wallet)
-        .await?;
+    let tx_hash = transaction::send_transaction(
+        &web3,
+        &contract,
+        contract_specs,
+        "deleteKey",
+        call_params,
+        options,
+    )
+    .await?;
 
     println!("⏳ Waiting for transaction {:?} to be mined...", tx_hash);
 
@@ -106,6 +99,9 @@ pub async fn delete_key_on_chain(
     if let Some(status) = receipt.status {
         if status == U64::one() {
             println!("🗑️ Key deleted on-chain. Tx hash: {:?}", tx_hash);
+            if let Some(link) = contract_specs.ctx.explorer_link(&tx_hash) {
+                println!("🔗 {link}");
+            }
             Ok(tx_hash)
         } else {
             Err(anyhow::anyhow!(
@@ -0,0 +1,207 @@
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use hkdf::Hkdf;
+use secp256k1::{PublicKey, Secp256k1, SecretKey, ecdh::SharedSecret};
+use sha2::Sha256;
+
+/// Marks data sealed by `seal`, followed by a format version.
+const MAGIC: &[u8; 4] = b"ZEC\x01";
+const PUBKEY_LEN: usize = 33;
+const NONCE_LEN: usize = 12;
+/// A content key wrapped for one recipient: 32 key bytes plus the AEAD tag.
+const WRAPPED_KEY_LEN: usize = 32 + 16;
+const HKDF_INFO: &[u8] = b"zaphenath-ecies-v1";
+
+// Envelope layout:
+//   MAGIC | ephemeral pubkey (33) | recipient count (1) | wrapped content keys (48 each)
+//   | nonce (12) | ChaCha20-Poly1305(content key, plaintext, aad = everything before the nonce)
+//
+// Each recipient's wrapping key is HKDF-SHA256 over the ECDH secret between the ephemeral
+// key and theirs. The envelope doesn't name its recipients; a reader tries each wrapped key.
+
+/// Parses a secp256k1 public key given as hex, compressed (33 bytes) or uncompressed
+/// (65 bytes, or 64 without the `04` prefix).
+pub fn parse_public_key(hex_key: &str) -> Result<PublicKey, anyhow::Error> {
+    let mut bytes = hex::decode(hex_key.trim().trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("Invalid public key hex: {hex_key}"))?;
+    if bytes.len() == 64 {
+        bytes.insert(0, 0x04);
+    }
+    PublicKey::from_slice(&bytes).map_err(|_| anyhow::anyhow!("Invalid public key: {hex_key}"))
+}
+
+/// The compressed public key for a secret key, as recipients hand it out.
+pub fn public_key(secret: &[u8; 32]) -> Result<PublicKey, anyhow::Error> {
+    let secret =
+        SecretKey::from_byte_array(*secret).map_err(|_| anyhow::anyhow!("Invalid secret key"))?;
+    Ok(PublicKey::from_secret_key(&Secp256k1::new(), &secret))
+}
+
+/// Whether `data` looks like an envelope produced by `seal`.
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypts `plaintext` so that any one of `recipients` can open it.
+pub fn seal(plaintext: &[u8], recipients: &[PublicKey]) -> Result<Vec<u8>, anyhow::Error> {
+    if recipients.is_empty() || recipients.len() > u8::MAX as usize {
+        return Err(anyhow::anyhow!(
+            "An envelope needs between 1 and {} recipients",
+            u8::MAX
+        ));
+    }
+
+    let secp = Secp256k1::new();
+    let ephemeral = random_secret_key();
+    let ephemeral_pub = PublicKey::from_secret_key(&secp, &ephemeral);
+    let content_key: [u8; 32] = rand::random();
+
+    let mut envelope = MAGIC.to_vec();
+    envelope.extend_from_slice(&ephemeral_pub.serialize());
+    envelope.push(recipients.len() as u8);
+    for recipient in recipients {
+        let shared = SharedSecret::new(recipient, &ephemeral);
+        let wrapping_key = wrapping_key(&shared, &ephemeral_pub, recipient);
+        // Each wrapping key is used exactly once, so a fixed nonce is safe here.
+        let wrapped = ChaCha20Poly1305::new(&wrapping_key)
+            .encrypt(&Nonce::default(), content_key.as_slice())
+            .map_err(|_| anyhow::anyhow!("Failed to wrap content key"))?;
+        envelope.extend_from_slice(&wrapped);
+    }
+
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&content_key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &envelope,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt payload"))?;
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// Decrypts an envelope with one recipient's secret key.
+pub fn open(envelope: &[u8], secret: &[u8; 32]) -> Result<Vec<u8>, anyhow::Error> {
+    let truncated = || anyhow::anyhow!("❌ Encrypted payload is truncated or corrupt");
+    if !is_sealed(envelope) {
+        return Err(anyhow::anyhow!("❌ Data is not an encrypted envelope"));
+    }
+
+    let mut offset = MAGIC.len();
+    let ephemeral_pub = envelope
+        .get(offset..offset + PUBKEY_LEN)
+        .and_then(|bytes| PublicKey::from_slice(bytes).ok())
+        .ok_or_else(truncated)?;
+    offset += PUBKEY_LEN;
+    let count = *envelope.get(offset).ok_or_else(truncated)? as usize;
+    offset += 1;
+    let wrapped_keys = envelope
+        .get(offset..offset + count * WRAPPED_KEY_LEN)
+        .ok_or_else(truncated)?;
+    offset += count * WRAPPED_KEY_LEN;
+    let (header, rest) = envelope.split_at(offset);
+    if rest.len() < NONCE_LEN {
+        return Err(truncated());
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let secret =
+        SecretKey::from_byte_array(*secret).map_err(|_| anyhow::anyhow!("Invalid secret key"))?;
+    let own_pub = PublicKey::from_secret_key(&Secp256k1::new(), &secret);
+    let shared = SharedSecret::new(&ephemeral_pub, &secret);
+    let unwrapper = ChaCha20Poly1305::new(&wrapping_key(&shared, &ephemeral_pub, &own_pub));
+
+    let content_key = wrapped_keys
+        .chunks(WRAPPED_KEY_LEN)
+        .find_map(|wrapped| unwrapper.decrypt(&Nonce::default(), wrapped).ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!("❌ This key is not a recipient of the encrypted payload")
+        })?;
+
+    ChaCha20Poly1305::new(Key::from_slice(&content_key))
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("❌ Encrypted payload failed authentication"))
+}
+
+fn wrapping_key(shared: &SharedSecret, ephemeral: &PublicKey, recipient: &PublicKey) -> Key {
+    let mut salt = ephemeral.serialize().to_vec();
+    salt.extend_from_slice(&recipient.serialize());
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(Some(&salt), shared.as_ref())
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn random_secret_key() -> SecretKey {
+    loop {
+        if let Ok(key) = SecretKey::from_byte_array(rand::random()) {
+            return key;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(byte: u8) -> ([u8; 32], PublicKey) {
+        let secret = [byte; 32];
+        (secret, public_key(&secret).unwrap())
+    }
+
+    #[test]
+    fn test_every_recipient_can_open() {
+        let (alice, alice_pub) = keypair(1);
+        let (bob, bob_pub) = keypair(2);
+        let envelope = seal(b"the secret", &[alice_pub, bob_pub]).unwrap();
+
+        assert!(is_sealed(&envelope));
+        assert_eq!(open(&envelope, &alice).unwrap(), b"the secret");
+        assert_eq!(open(&envelope, &bob).unwrap(), b"the secret");
+    }
+
+    #[test]
+    fn test_others_cannot_open() {
+        let (_, alice_pub) = keypair(1);
+        let (mallory, _) = keypair(3);
+        let envelope = seal(b"the secret", &[alice_pub]).unwrap();
+        assert!(open(&envelope, &mallory).is_err());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let (alice, alice_pub) = keypair(1);
+        let mut envelope = seal(b"the secret", &[alice_pub]).unwrap();
+        let last = envelope.len() - 1;
+        envelope[last] ^= 1;
+        assert!(open(&envelope, &alice).is_err());
+        assert!(open(&envelope[..40], &alice).is_err());
+    }
+
+    #[test]
+    fn test_parse_public_key_formats() {
+        let (_, public) = keypair(1);
+        let compressed = hex::encode(public.serialize());
+        let uncompressed = hex::encode(public.serialize_uncompressed());
+        assert_eq!(parse_public_key(&compressed).unwrap(), public);
+        assert_eq!(
+            parse_public_key(&format!("0x{uncompressed}")).unwrap(),
+            public
+        );
+        assert_eq!(parse_public_key(&uncompressed[2..]).unwrap(), public);
+        assert!(parse_public_key("0x1234").is_err());
+    }
+}
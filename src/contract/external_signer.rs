@@ -0,0 +1,170 @@
+use super::signer::{Signer, TransactionRequest};
+use futures::{FutureExt, future::BoxFuture};
+use serde_json::Value;
+use std::str::FromStr;
+use web3::{
+    Transport,
+    transports::{Http, Ipc},
+    types::{Address, Bytes},
+};
+
+/// An external signer (e.g. Clef) holding the key for `address`, reached over JSON-RPC at
+/// an http(s) URL or a Unix socket path. zaph only ever sends it unsigned transactions.
+#[derive(Debug)]
+pub struct ClefSigner {
+    url: String,
+    address: Address,
+}
+
+impl ClefSigner {
+    pub fn new(url: &str, address: &str) -> Result<Self, anyhow::Error> {
+        let address = Address::from_str(address.trim_start_matches("0x"))
+            .map_err(|_| anyhow::anyhow!("Invalid external signer address: {address}"))?;
+        Ok(ClefSigner {
+            url: url.to_string(),
+            address,
+        })
+    }
+
+    async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value, anyhow::Error> {
+        let url = &self.url;
+        if url.starts_with("http://") || url.starts_with("https://") {
+            Http::new(url)?.execute(method, params).await
+        } else {
+            Ipc::new(url).await?.execute(method, params).await
+        }
+        .map_err(|e| anyhow::anyhow!("External signer at {url} refused {method}: {e}"))
+    }
+}
+
+impl Signer for ClefSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        tx: &'a TransactionRequest,
+    ) -> BoxFuture<'a, Result<Bytes, anyhow::Error>> {
+        async move {
+            let response = self
+                .call("account_signTransaction", vec![serde_json::to_value(tx)?])
+                .await?;
+            parse_signed(&response)
+        }
+        .boxed()
+    }
+
+    fn sign_message<'a>(
+        &'a self,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, anyhow::Error>> {
+        async move {
+            let params = vec![
+                Value::from("text/plain"),
+                Value::from(format!("{:#x}", self.address)),
+                Value::from(format!("0x{}", hex::encode(message))),
+            ];
+            let response = self.call("account_signData", params).await?;
+            let signature = response
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("External signer returned no signature"))?;
+            hex::decode(signature.trim_start_matches("0x"))
+                .map_err(|_| anyhow::anyhow!("External signer returned an invalid signature"))
+        }
+        .boxed()
+    }
+}
+
+/// Extracts the raw transaction from an `account_signTransaction` result (`{"raw": "0x..", "tx": {..}}`).
+fn parse_signed(response: &Value) -> Result<Bytes, anyhow::Error> {
+    let raw = response
+        .get("raw")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("External signer response has no 'raw' transaction"))?;
+    let bytes = hex::decode(raw.trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("External signer returned an invalid raw transaction"))?;
+    Ok(Bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use web3::types::U256;
+
+    /// A stand-in signer that answers a single `account_signTransaction` request over HTTP.
+    fn spawn_stand_in_signer() -> (String, std::thread::JoinHandle<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "raw": "0xf86b01", "tx": {} },
+            })
+            .to_string();
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            request
+        });
+
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_sign_transaction_over_http() {
+        let (url, stand_in) = spawn_stand_in_signer();
+        let tx = TransactionRequest {
+            from: Address::from_low_u64_be(1),
+            to: Address::from_low_u64_be(2),
+            gas: U256::from(21_000),
+            gas_price: U256::from(1_000_000_000u64),
+            value: U256::zero(),
+            nonce: U256::from(7),
+            data: Bytes(vec![0xab]),
+            chain_id: U256::from(31337),
+        };
+
+        let signer = ClefSigner::new(&url, "0x0000000000000000000000000000000000000001").unwrap();
+        let raw = signer.sign_transaction(&tx).await.unwrap();
+        assert_eq!(raw.0, vec![0xf8, 0x6b, 0x01]);
+
+        let request = stand_in.join().unwrap();
+        assert_eq!(request["method"], "account_signTransaction");
+        assert_eq!(request["params"][0]["nonce"], "0x7");
+        assert_eq!(request["params"][0]["chainId"], "0x7a69");
+        assert_eq!(request["params"][0]["data"], "0xab");
+    }
+
+    #[test]
+    fn test_missing_raw_is_an_error() {
+        assert!(parse_signed(&serde_json::json!({ "tx": {} })).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! On-chain key identifiers.
+//!
+//! The contract addresses a key by a `bytes32` keyId. By default it is `keccak256(label)`, which
+//! anyone can recompute from a guessable label ("bitcoin-seed", "will") to find and watch the key.
+//! A salted ID is instead `HMAC-SHA256(owner secret, label)`, where the owner secret is derived
+//! from the owner's signature over a fixed message: only the owner can compute it, and the same
+//! signer derives the same ID again should the config be lost.
+
+use super::signer::Signer;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use web3::types::{Address, H256};
+
+/// The message the owner signs to derive their key ID secret. Changing it changes every salted ID.
+const SECRET_MESSAGE: &[u8] = b"Zaphenath key ID secret v1";
+
+/// How a new key's on-chain ID is chosen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scheme {
+    /// `keccak256(label)`, recomputed from the label whenever it's needed.
+    Plain,
+    /// Derived from the label and the owner's signer, see [`salted`].
+    Salted,
+    /// A ready-made keyId, e.g. from a key created by another client.
+    Raw(H256),
+}
+
+impl Scheme {
+    pub fn from_args(key_hash: Option<H256>, salted_id: bool) -> Self {
+        match (key_hash, salted_id) {
+            (Some(hash), _) => Scheme::Raw(hash),
+            (None, true) => Scheme::Salted,
+            (None, false) => Scheme::Plain,
+        }
+    }
+
+    /// The keyId for `label` under this scheme. Only a salted ID needs the owner's signer.
+    pub async fn key_hash(
+        &self,
+        label: &str,
+        signer: impl FnOnce() -> Result<std::sync::Arc<dyn Signer>, anyhow::Error>,
+    ) -> Result<H256, anyhow::Error> {
+        match self {
+            Scheme::Plain => Ok(plain(label)),
+            Scheme::Salted => Ok(salted(&owner_secret(signer()?.as_ref()).await?, label)),
+            Scheme::Raw(hash) => Ok(*hash),
+        }
+    }
+}
+
+/// The default, public keyId for `label`.
+pub fn plain(label: &str) -> H256 {
+    H256::from(web3::signing::keccak256(label.as_bytes()))
+}
+
+/// The salted keyId for `label` under an owner secret.
+pub fn salted(secret: &[u8; 32], label: &str) -> H256 {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(label.as_bytes());
+    H256::from_slice(&mac.finalize().into_bytes())
+}
+
+/// Derives the owner's key ID secret by asking their signer to sign [`SECRET_MESSAGE`].
+/// Deterministic for local keys (RFC 6979 signatures); external signers should be too.
+pub async fn owner_secret(signer: &dyn Signer) -> Result<[u8; 32], anyhow::Error> {
+    let signature = signer
+        .sign_message(SECRET_MESSAGE)
+        .await
+        .map_err(|e| anyhow::anyhow!("❌ Could not derive the key ID secret: {e}"))?;
+    secret_from_signature(&signature)
+}
+
+/// Hashes a 65-byte `r | s | v` signature into the owner secret. Signers disagree on `v`
+/// (27/28 or 0/1), so it's brought to 27/28, as local keys give it, before hashing: the same
+/// key must derive the same IDs whichever backend holds it.
+fn secret_from_signature(signature: &[u8]) -> Result<[u8; 32], anyhow::Error> {
+    if signature.len() != 65 {
+        return Err(anyhow::anyhow!(
+            "❌ Could not derive the key ID secret: expected a 65-byte signature, got {} bytes",
+            signature.len()
+        ));
+    }
+    let (rs, v) = (&signature[..64], signature[64]);
+    let v = match v {
+        0 | 1 => v + 27,
+        v => v,
+    };
+    Ok(web3::signing::keccak256(&[rs, &[v]].concat()))
+}
+
+/// The contract's storage key for `key_hash` under `owner`, as `getFullKey` computes it:
+/// `keccak256(abi.encodePacked(keyId, owner))`.
+pub fn full_key(key_hash: H256, owner: Address) -> H256 {
+    let mut packed = key_hash.as_bytes().to_vec();
+    packed.extend_from_slice(owner.as_bytes());
+    H256::from(web3::signing::keccak256(&packed))
+}
+
+/// Parses a `0x`-prefixed 32-byte key hash.
+pub fn parse(s: &str) -> Result<H256, anyhow::Error> {
+    let bytes = hex::decode(s.trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("❌ Invalid key hash '{s}': not hex"))?;
+    if bytes.len() != 32 {
+        return Err(anyhow::anyhow!(
+            "❌ Invalid key hash '{s}': expected 32 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::signer::{LocalKeySigner, parse_hex_key};
+
+    const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[test]
+    fn test_plain_matches_keccak_of_label() {
+        assert_eq!(
+            format!("{:#x}", plain("my-key")),
+            format!(
+                "0x{}",
+                hex::encode(web3::signing::keccak256("my-key".as_bytes()))
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_salted_id_is_stable_per_owner_and_hides_label() {
+        let signer = LocalKeySigner::new(parse_hex_key(KEY).unwrap());
+        let secret = owner_secret(&signer).await.unwrap();
+        assert_eq!(secret, owner_secret(&signer).await.unwrap());
+
+        let id = salted(&secret, "my-key");
+        assert_ne!(id, plain("my-key"));
+        assert_ne!(id, salted(&secret, "other-key"));
+
+        let other = LocalKeySigner::new(parse_hex_key(&"11".repeat(32)).unwrap());
+        let other_secret = owner_secret(&other).await.unwrap();
+        assert_ne!(id, salted(&other_secret, "my-key"));
+    }
+
+    #[tokio::test]
+    async fn test_secret_ignores_how_the_signer_encodes_v() {
+        let signer = LocalKeySigner::new(parse_hex_key(KEY).unwrap());
+        let mut signature = signer.sign_message(SECRET_MESSAGE).await.unwrap();
+        let secret = owner_secret(&signer).await.unwrap();
+        assert_eq!(secret_from_signature(&signature).unwrap(), secret);
+
+        // As an external signer might return it
+        signature[64] -= 27;
+        assert_eq!(secret_from_signature(&signature).unwrap(), secret);
+        assert!(secret_from_signature(&signature[..64]).is_err());
+    }
+
+    #[test]
+    fn test_parse_round_trips_and_rejects_bad_lengths() {
+        let id = plain("my-key");
+        assert_eq!(parse(&format!("{id:#x}")).unwrap(), id);
+        assert!(parse("0x1234").is_err());
+        assert!(parse("0xzz").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use dialoguer::Password;
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+use web3::signing::SecretKey;
+
+/// Environment variable holding the keystore passphrase, for non-interactive use.
+pub const PASSWORD_ENV: &str = "ZAPHENATH_KEYSTORE_PASSWORD";
+
+/// Environment variable pointing at a file whose first line is the keystore passphrase.
+/// Set by the global `--password-file` option.
+pub const PASSWORD_FILE_ENV: &str = "ZAPHENATH_PASSWORD_FILE";
+
+/// Whether the contents of a key file look like a Web3 Secret Storage (v3) keystore
+/// rather than a plain hex private key.
+pub fn is_keystore(contents: &str) -> bool {
+    contents.trim_start().starts_with('{')
+}
+
+/// Resolves the passphrase for a keystore: `--password-file` first, then
+/// `ZAPHENATH_KEYSTORE_PASSWORD`, then an interactive prompt.
+pub fn read_passphrase(keystore_path: &str) -> Result<String, anyhow::Error> {
+    if let Ok(path) = std::env::var(PASSWORD_FILE_ENV) {
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read password file {path}: {e}"))?;
+        return Ok(contents.lines().next().unwrap_or_default().to_string());
+    }
+
+    if let Ok(passphrase) = std::env::var(PASSWORD_ENV) {
+        return Ok(passphrase);
+    }
+
+    Password::new()
+        .with_prompt(format!("🔐 Passphrase for {keystore_path}"))
+        .interact()
+        .map_err(|e| anyhow::anyhow!("Failed to read passphrase: {e}"))
+}
+
+/// Like `read_passphrase`, but an interactive prompt asks twice, for a keystore being created.
+pub fn new_passphrase(keystore_path: &str) -> Result<String, anyhow::Error> {
+    if std::env::var(PASSWORD_FILE_ENV).is_ok() || std::env::var(PASSWORD_ENV).is_ok() {
+        return read_passphrase(keystore_path);
+    }
+
+    Password::new()
+        .with_prompt(format!("🔐 New passphrase for {keystore_path}"))
+        .with_confirmation("🔐 Repeat passphrase", "❌ Passphrases don't match")
+        .interact()
+        .map_err(|e| anyhow::anyhow!("Failed to read passphrase: {e}"))
+}
+
+/// Encrypts `key` into a new v3 keystore (scrypt) at `keystore_path`, readable only by the owner.
+pub fn encrypt(
+    keystore_path: &str,
+    key: &SecretKey,
+    passphrase: &str,
+) -> Result<(), anyhow::Error> {
+    let path = Path::new(keystore_path);
+    if path.exists() {
+        return Err(anyhow::anyhow!("❌ {keystore_path} already exists"));
+    }
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("❌ Invalid keystore path: {keystore_path}"))?;
+
+    eth_keystore::encrypt_key(
+        dir.unwrap_or(Path::new(".")),
+        &mut rand::thread_rng(),
+        key.secret_bytes(),
+        passphrase,
+        Some(name),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to write keystore {keystore_path}: {e}"))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+/// Decrypts a v3 keystore (scrypt or pbkdf2) into a signing key.
+pub fn decrypt(keystore_path: &str, passphrase: &str) -> Result<SecretKey, anyhow::Error> {
+    let bytes = eth_keystore::decrypt_key(keystore_path, passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt keystore {keystore_path}: {e}"))?;
+    SecretKey::from_slice(&bytes)
+        .map_err(|_| anyhow::anyhow!("Keystore {keystore_path} does not hold a valid private key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_decrypts_scrypt_keystore() {
+        let dir = tempdir().unwrap();
+        let pk = [0x11u8; 32];
+        eth_keystore::encrypt_key(
+            dir.path(),
+            &mut rand::thread_rng(),
+            pk,
+            "correct horse",
+            Some("key.json"),
+        )
+        .unwrap();
+        let path = dir.path().join("key.json");
+        let path = path.to_str().unwrap();
+
+        assert!(is_keystore(&fs::read_to_string(path).unwrap()));
+        let sk = decrypt(path, "correct horse").unwrap();
+        assert_eq!(sk.secret_bytes(), pk);
+        assert!(decrypt(path, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_round_trips_and_is_owner_only() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("new.json");
+        let path = path.to_str().unwrap();
+        let key = SecretKey::from_slice(&[0x22u8; 32]).unwrap();
+
+        encrypt(path, &key, "pw").unwrap();
+        assert_eq!(decrypt(path, "pw").unwrap(), key);
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(encrypt(path, &key, "pw").is_err(), "must not overwrite");
+    }
+
+    #[test]
+    fn test_decrypts_pbkdf2_keystore() {
+        // Test vector from the Web3 Secret Storage definition, passphrase "testpassword".
+        let keystore = r#"{
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": {"iv": "6087dab2f9fdbbfaddc31a909735c1e6"},
+                "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                "kdf": "pbkdf2",
+                "kdfparams": {
+                    "c": 262144,
+                    "dklen": 32,
+                    "prf": "hmac-sha256",
+                    "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                },
+                "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+            },
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "version": 3
+        }"#;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("key.json");
+        fs::write(&path, keystore).unwrap();
+
+        let sk = decrypt(path.to_str().unwrap(), "testpassword").unwrap();
+        assert_eq!(
+            hex::encode(sk.secret_bytes()),
+            "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use super::keystore;
+use bip39::Mnemonic;
+use dialoguer::Password;
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey as Secp256k1SecretKey};
+use sha2::Sha512;
+use std::fs;
+use web3::signing::SecretKey;
+
+/// Environment variable pointing at the file holding the seed phrase.
+/// Set by the global `--mnemonic-file` option.
+pub const MNEMONIC_FILE_ENV: &str = "ZAPHENATH_MNEMONIC_FILE";
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// Reads the seed phrase from the `--mnemonic-file` (plain text, or a v3 keystore
+/// holding the phrase), falling back to an interactive prompt.
+pub fn read_mnemonic() -> Result<String, anyhow::Error> {
+    let Ok(path) = std::env::var(MNEMONIC_FILE_ENV) else {
+        return Password::new()
+            .with_prompt("🌱 Mnemonic phrase")
+            .interact()
+            .map_err(|e| anyhow::anyhow!("Failed to read mnemonic: {e}"));
+    };
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read mnemonic file {path}: {e}"))?;
+    if !keystore::is_keystore(&contents) {
+        return Ok(contents.trim().to_string());
+    }
+
+    let passphrase = keystore::read_passphrase(&path)?;
+    let bytes = eth_keystore::decrypt_key(&path, passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt mnemonic file {path}: {e}"))?;
+    String::from_utf8(bytes)
+        .map(|phrase| phrase.trim().to_string())
+        .map_err(|_| anyhow::anyhow!("Mnemonic file {path} does not hold a valid phrase"))
+}
+
+/// Parses a BIP-32 derivation path such as `m/44'/60'/0'/0/3`.
+pub fn parse_path(path: &str) -> Result<Vec<u32>, anyhow::Error> {
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        return Err(anyhow::anyhow!(
+            "Invalid derivation path '{path}': must start with 'm/'"
+        ));
+    }
+
+    segments
+        .map(|segment| {
+            let (index, hardened) = match segment.strip_suffix(['\'', 'h']) {
+                Some(index) => (index, true),
+                None => (segment, false),
+            };
+            let index: u32 = index
+                .parse()
+                .ok()
+                .filter(|i| *i < HARDENED)
+                .ok_or_else(|| anyhow::anyhow!("Invalid segment '{segment}' in path '{path}'"))?;
+            Ok(if hardened { index | HARDENED } else { index })
+        })
+        .collect()
+}
+
+/// Derives the secret key at `path` from a BIP-39 seed phrase (no BIP-39 passphrase).
+pub fn derive_secret_key(phrase: &str, path: &str) -> Result<SecretKey, anyhow::Error> {
+    let mnemonic =
+        Mnemonic::parse_normalized(phrase).map_err(|e| anyhow::anyhow!("Invalid mnemonic: {e}"))?;
+    let seed = mnemonic.to_seed("");
+
+    let (mut key, mut chain_code) = split(hmac_sha512(b"Bitcoin seed", &seed))?;
+    let secp = Secp256k1::signing_only();
+
+    for index in parse_path(path)? {
+        let mut data = Vec::with_capacity(37);
+        if index & HARDENED != 0 {
+            data.push(0);
+            data.extend_from_slice(&key.secret_bytes());
+        } else {
+            data.extend_from_slice(&PublicKey::from_secret_key(&secp, &key).serialize());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, next_chain_code) = split(hmac_sha512(&chain_code, &data))?;
+        key = key
+            .add_tweak(&Scalar::from(tweak))
+            .map_err(|_| anyhow::anyhow!("Derivation path '{path}' yields an invalid key"))?;
+        chain_code = next_chain_code;
+    }
+
+    SecretKey::from_slice(&key.secret_bytes())
+        .map_err(|_| anyhow::anyhow!("Derivation path '{path}' yields an invalid key"))
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Splits an HMAC output into its key half and chain code half.
+fn split(bytes: [u8; 64]) -> Result<(Secp256k1SecretKey, [u8; 32]), anyhow::Error> {
+    let (left, right) = bytes.split_at(32);
+    let key = Secp256k1SecretKey::from_slice(left)
+        .map_err(|_| anyhow::anyhow!("Derived an invalid key, try the next index"))?;
+    Ok((key, right.try_into().expect("32 bytes")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web3::signing::{Key, SecretKeyRef};
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_derives_well_known_address() {
+        let sk = derive_secret_key(PHRASE, "m/44'/60'/0'/0/0").unwrap();
+        assert_eq!(
+            format!("{:#x}", SecretKeyRef::new(&sk).address()),
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+        );
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("m/44'/60'/0'/0/3").unwrap(),
+            vec![44 | HARDENED, 60 | HARDENED, HARDENED, 0, 3]
+        );
+        assert!(parse_path("44'/60'").is_err());
+        assert!(parse_path("m/x").is_err());
+    }
+}
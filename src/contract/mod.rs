@@ -1,9 +1,22 @@
+pub mod agent;
 pub mod create_key;
 pub mod delete_key;
+pub mod ecies;
+pub mod external_signer;
+pub mod key_id;
+pub mod keystore;
+pub mod mnemonic;
 pub mod network;
+pub mod nonce;
+pub mod padding;
+pub mod passphrase;
+pub mod payload;
 pub mod ping;
 pub mod read;
 pub mod remove_custodian;
 pub mod set_custodian;
+pub mod shamir;
+pub mod signer;
+pub mod transaction;
 pub mod types;
 pub mod update;
@@ -1,37 +1,218 @@
 use super::types::NetworkContext;
-use std::collections::HashMap;
+use crate::config::{NetworkEntry, get_config_path, read_config};
+use web3::{Transport, Web3};
 
-/// Resolves a known network name to an RPC URL
-pub fn resolve_rpc_url(network: &str) -> Option<String> {
-    let mut map = HashMap::new();
+/// Networks available out of the box. Entries in the config with the same name take precedence.
+pub fn builtin_networks() -> Vec<NetworkEntry> {
+    let entry =
+        |name: &str, rpc_urls: &[&str], chain_id: u64, explorer_url: Option<&str>| NetworkEntry {
+            name: name.to_string(),
+            rpc_urls: rpc_urls.iter().map(|s| s.to_string()).collect(),
+            chain_id,
+            explorer_url: explorer_url.map(str::to_string),
+        };
 
-    map.insert("mainnet", "https://mainnet.infura.io/v3/YOUR_PROJECT_ID");
-    map.insert("sepolia", "https://sepolia.infura.io/v3/YOUR_PROJECT_ID");
-    map.insert("goerli", "https://goerli.infura.io/v3/YOUR_PROJECT_ID");
-    map.insert("localhost", "http://localhost:8545");
+    vec![
+        entry(
+            "mainnet",
+            &[
+                "https://mainnet.infura.io/v3/${INFURA_API_KEY}",
+                "https://ethereum-rpc.publicnode.com",
+            ],
+            1,
+            Some("https://etherscan.io/tx/{tx}"),
+        ),
+        entry(
+            "sepolia",
+            &[
+                "https://sepolia.infura.io/v3/${INFURA_API_KEY}",
+                "https://ethereum-sepolia-rpc.publicnode.com",
+            ],
+            11155111,
+            Some("https://sepolia.etherscan.io/tx/{tx}"),
+        ),
+        entry(
+            "holesky",
+            &[
+                "https://holesky.infura.io/v3/${INFURA_API_KEY}",
+                "https://ethereum-holesky-rpc.publicnode.com",
+            ],
+            17000,
+            Some("https://holesky.etherscan.io/tx/{tx}"),
+        ),
+        entry("anvil", &["http://localhost:8545"], 31337, None),
+        entry("localhost", &["http://localhost:8545"], 31337, None),
+    ]
+}
+
+/// The built-in networks merged with the user's own definitions.
+#[derive(Debug, Clone)]
+pub struct NetworkRegistry {
+    networks: Vec<NetworkEntry>,
+}
+
+impl NetworkRegistry {
+    pub fn new(user_networks: Vec<NetworkEntry>) -> Self {
+        let mut networks: Vec<NetworkEntry> = builtin_networks()
+            .into_iter()
+            .filter(|b| !user_networks.iter().any(|u| u.name == b.name))
+            .collect();
+        networks.extend(user_networks);
+        NetworkRegistry { networks }
+    }
 
-    map.get(network).map(|s| s.to_string())
+    /// Loads the registry using the networks defined in the default config file.
+    pub fn load() -> Result<Self, String> {
+        let config = read_config(&get_config_path())
+            .map_err(|e| format!("Failed to read network registry from config: {e}"))?;
+        Ok(Self::new(config.networks))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NetworkEntry> {
+        self.networks.iter().find(|n| n.name == name)
+    }
+
+    pub fn networks(&self) -> &[NetworkEntry] {
+        &self.networks
+    }
+}
+
+impl NetworkEntry {
+    /// Returns the first RPC URL whose `${ENV}` references can all be expanded.
+    pub fn resolve_rpc_url(&self) -> Result<String, String> {
+        let mut errors = vec![];
+        for url in &self.rpc_urls {
+            match shellexpand::env(url) {
+                Ok(expanded) => return Ok(expanded.into_owned()),
+                Err(e) => errors.push(format!("{url}: {e}")),
+            }
+        }
+        Err(format!(
+            "No usable RPC URL for network '{}' ({})",
+            self.name,
+            errors.join("; ")
+        ))
+    }
 }
 
 pub fn build_network_context(
     rpc_url_opt: Option<&str>,
     network_opt: Option<&str>,
 ) -> Result<NetworkContext, String> {
+    let registry = NetworkRegistry::load()?;
+    build_network_context_with(&registry, rpc_url_opt, network_opt)
+}
+
+pub fn build_network_context_with(
+    registry: &NetworkRegistry,
+    rpc_url_opt: Option<&str>,
+    network_opt: Option<&str>,
+) -> Result<NetworkContext, String> {
+    let known = network_opt.and_then(|n| registry.get(n));
+
     if let Some(url) = rpc_url_opt {
         return Ok(NetworkContext {
             network: network_opt.map(|s| s.to_string()),
             rpc_url: url.to_string(),
+            explorer_url: known.and_then(|n| n.explorer_url.clone()),
+            chain_id: known.map(|n| n.chain_id),
         });
     }
 
     if let Some(network) = network_opt {
-        let resolved =
-            resolve_rpc_url(network).ok_or_else(|| format!("Unknown network name: {}", network))?;
+        let entry = known.ok_or_else(|| format!("Unknown network name: {}", network))?;
         return Ok(NetworkContext {
             network: Some(network.to_string()),
-            rpc_url: resolved,
+            rpc_url: entry.resolve_rpc_url()?,
+            explorer_url: entry.explorer_url.clone(),
+            chain_id: Some(entry.chain_id),
         });
     }
 
     Err("You must provide either --rpc-url or --network".to_string())
 }
+
+/// Asks the RPC endpoint which chain it is on and refuses to continue if it isn't the
+/// chain the context expects. Returns the reported chain ID so callers can record it.
+pub async fn verify_chain_id<T: Transport>(
+    web3: &Web3<T>,
+    ctx: &NetworkContext,
+) -> Result<u64, anyhow::Error> {
+    let actual = web3
+        .eth()
+        .chain_id()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to query chain ID from {}: {e}", ctx.rpc_url))?
+        .as_u64();
+    check_chain_id(ctx, actual)?;
+    Ok(actual)
+}
+
+fn check_chain_id(ctx: &NetworkContext, actual: u64) -> Result<(), anyhow::Error> {
+    match ctx.chain_id {
+        Some(expected) if expected != actual => Err(anyhow::anyhow!(
+            "❌ Chain ID mismatch: {} reports chain {actual}, but {} expects chain {expected}. Refusing to sign.",
+            ctx.rpc_url,
+            ctx.network.as_deref().unwrap_or("this key"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anvil_is_builtin() {
+        let registry = NetworkRegistry::new(vec![]);
+        let ctx = build_network_context_with(&registry, None, Some("anvil")).unwrap();
+        assert_eq!(ctx.rpc_url, "http://localhost:8545");
+        assert_eq!(ctx.chain_id, Some(31337));
+    }
+
+    #[test]
+    fn test_user_network_overrides_builtin_and_skips_unset_env() {
+        let registry = NetworkRegistry::new(vec![NetworkEntry {
+            name: "sepolia".to_string(),
+            rpc_urls: vec![
+                "https://rpc.example/${ZAPH_TEST_SURELY_UNSET_VAR}".to_string(),
+                "https://fallback.example".to_string(),
+            ],
+            chain_id: 11155111,
+            explorer_url: None,
+        }]);
+
+        let ctx = build_network_context_with(&registry, None, Some("sepolia")).unwrap();
+        assert_eq!(ctx.rpc_url, "https://fallback.example");
+        assert_eq!(ctx.explorer_url, None);
+    }
+
+    #[test]
+    fn test_explicit_rpc_url_wins_but_keeps_registry_metadata() {
+        let registry = NetworkRegistry::new(vec![]);
+        let ctx = build_network_context_with(&registry, Some("http://node:8545"), Some("mainnet"))
+            .unwrap();
+        assert_eq!(ctx.rpc_url, "http://node:8545");
+        assert!(ctx.explorer_url.is_some());
+    }
+
+    #[test]
+    fn test_chain_id_mismatch_is_rejected() {
+        let registry = NetworkRegistry::new(vec![]);
+        let ctx = build_network_context_with(&registry, Some("http://node:8545"), Some("sepolia"))
+            .unwrap();
+        assert!(check_chain_id(&ctx, 11155111).is_ok());
+        assert!(check_chain_id(&ctx, 1).is_err());
+
+        let unpinned =
+            build_network_context_with(&registry, Some("http://node:8545"), None).unwrap();
+        assert!(check_chain_id(&unpinned, 1).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_network_fails() {
+        let registry = NetworkRegistry::new(vec![]);
+        assert!(build_network_context_with(&registry, None, Some("nope")).is_err());
+    }
+}
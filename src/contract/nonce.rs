@@ -0,0 +1,142 @@
+//! Nonce assignment for an account sending several transactions at once, e.g. the daemon
+//! pinging many keys signed by the same key.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use web3::types::{Address, U256};
+
+/// The next nonce we'd give an account on a chain, `None` until asked of the node.
+type NextNonce = Arc<AsyncMutex<Option<U256>>>;
+
+/// Hands out sequential nonces per signer and chain. Sends from one account are serialized
+/// from reserving a nonce until the transaction is broadcast, so two transactions never get
+/// the same nonce, while waiting for receipts still overlaps.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    accounts: Mutex<HashMap<(U256, Address), NextNonce>>,
+}
+
+/// A nonce held for one transaction. Other sends from the account wait until it is committed
+/// (the transaction was broadcast) or dropped (it wasn't, and the nonce is free again).
+#[derive(Debug)]
+pub struct NonceReservation {
+    pub nonce: U256,
+    next: OwnedMutexGuard<Option<U256>>,
+}
+
+impl NonceReservation {
+    pub fn commit(mut self) {
+        *self.next = Some(self.nonce + 1);
+    }
+}
+
+impl NonceManager {
+    /// Reserves the next nonce for `from` on `chain_id`. `pending` is the node's pending
+    /// transaction count, which wins when it's ahead of ours (e.g. another wallet sent from
+    /// the account).
+    pub async fn reserve(
+        &self,
+        chain_id: U256,
+        from: Address,
+        pending: impl Future<Output = Result<U256, anyhow::Error>>,
+    ) -> Result<NonceReservation, anyhow::Error> {
+        let slot = self
+            .accounts
+            .lock()
+            .unwrap()
+            .entry((chain_id, from))
+            .or_default()
+            .clone();
+        let next = slot.lock_owned().await;
+        let pending = pending.await?;
+        Ok(NonceReservation {
+            nonce: next.map_or(pending, |next| next.max(pending)),
+            next,
+        })
+    }
+
+    /// Drops what we know about `from`'s nonces after one of its transactions failed. If it
+    /// never made it into a block, its nonce is a gap the node's pending count points back
+    /// to, and the next reservation fills it.
+    pub async fn forget(&self, from: Address) {
+        let slots: Vec<_> = self
+            .accounts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((_, address), _)| *address == from)
+            .map(|(_, slot)| slot.clone())
+            .collect();
+        for slot in slots {
+            *slot.lock().await = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(count: u64) -> impl Future<Output = Result<U256, anyhow::Error>> {
+        futures::future::ready(Ok(U256::from(count)))
+    }
+
+    #[tokio::test]
+    async fn test_nonces_are_sequential_per_account_and_chain() {
+        let nonces = NonceManager::default();
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let chain = U256::one();
+
+        // The node hasn't seen our broadcasts yet, so its count lags behind
+        for expected in 5..8 {
+            let reservation = nonces.reserve(chain, alice, pending(5)).await.unwrap();
+            assert_eq!(reservation.nonce, U256::from(expected));
+            reservation.commit();
+        }
+        assert_eq!(
+            nonces.reserve(chain, bob, pending(0)).await.unwrap().nonce,
+            U256::zero()
+        );
+        assert_eq!(
+            nonces
+                .reserve(U256::from(2), alice, pending(9))
+                .await
+                .unwrap()
+                .nonce,
+            U256::from(9)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsent_and_failed_transactions_leave_no_gap() {
+        let nonces = NonceManager::default();
+        let alice = Address::repeat_byte(1);
+        let chain = U256::one();
+
+        nonces
+            .reserve(chain, alice, pending(3))
+            .await
+            .unwrap()
+            .commit();
+        // Not broadcast: the nonce is handed out again
+        drop(nonces.reserve(chain, alice, pending(3)).await.unwrap());
+        let reservation = nonces.reserve(chain, alice, pending(3)).await.unwrap();
+        assert_eq!(reservation.nonce, U256::from(4));
+        reservation.commit();
+
+        // Broadcast but dropped by the node: after a failure we go back to its count
+        nonces.forget(alice).await;
+        assert_eq!(
+            nonces
+                .reserve(chain, alice, pending(4))
+                .await
+                .unwrap()
+                .nonce,
+            U256::from(4)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+/// Marks padded key data, followed by a format version.
+const MAGIC: &[u8; 4] = b"ZPD\x01";
+const HEADER_LEN: usize = MAGIC.len() + 4;
+
+/// The buckets used when `--pad` is given without sizes.
+pub const DEFAULT_BUCKETS: [usize; 3] = [256, 1024, 4096];
+
+// Layout:
+//   MAGIC | data length (u32 BE) | data | zeros up to the bucket size
+//
+// The explicit length makes stripping unambiguous whatever the data ends with.
+
+/// Whether key data was padded by `pad`: it has the header, its declared length fits, and
+/// everything after the data is zeros. Raw data merely starting with the magic isn't taken
+/// for padded.
+pub fn is_padded(data: &[u8]) -> bool {
+    padded_len(data).is_some_and(|len| data[HEADER_LEN + len..].iter().all(|b| *b == 0))
+}
+
+/// The data length declared by the padding header, if it fits in `data`.
+fn padded_len(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(MAGIC) || data.len() < HEADER_LEN {
+        return None;
+    }
+    let len =
+        u32::from_be_bytes(data[MAGIC.len()..HEADER_LEN].try_into().expect("4 bytes")) as usize;
+    (HEADER_LEN + len <= data.len()).then_some(len)
+}
+
+/// Pads `data` (with its header) to the smallest bucket it fits in. Data larger than every
+/// bucket is padded to a multiple of the largest one.
+pub fn pad(data: &[u8], buckets: &[usize]) -> Result<Vec<u8>, anyhow::Error> {
+    let len =
+        u32::try_from(data.len()).map_err(|_| anyhow::anyhow!("❌ Data is too large to pad"))?;
+    let needed = HEADER_LEN + data.len();
+
+    let mut buckets = buckets.to_vec();
+    buckets.sort_unstable();
+    let largest = *buckets
+        .last()
+        .filter(|largest| **largest > 0)
+        .ok_or_else(|| anyhow::anyhow!("❌ No padding bucket sizes given"))?;
+    let size = buckets
+        .iter()
+        .copied()
+        .find(|bucket| *bucket >= needed)
+        .unwrap_or_else(|| needed.div_ceil(largest) * largest);
+
+    let mut padded = Vec::with_capacity(size);
+    padded.extend_from_slice(MAGIC);
+    padded.extend_from_slice(&len.to_be_bytes());
+    padded.extend_from_slice(data);
+    padded.resize(size, 0);
+    Ok(padded)
+}
+
+/// Strips the padding added by `pad`.
+pub fn unpad(padded: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    if !is_padded(padded) {
+        return Err(anyhow::anyhow!("❌ Data is not padded"));
+    }
+    let len = padded_len(padded).expect("checked by is_padded");
+    Ok(padded[HEADER_LEN..HEADER_LEN + len].to_vec())
+}
+
+/// Parses a bucket size such as `256`, `1k` or `4KiB`.
+pub fn parse_size(size: &str) -> Result<usize, anyhow::Error> {
+    let lower = size.trim().to_lowercase();
+    let (number, multiplier) = match lower.trim_end_matches("ib").trim_end_matches('b') {
+        n if n.ends_with('k') => (&n[..n.len() - 1], 1024),
+        n if n.ends_with('m') => (&n[..n.len() - 1], 1024 * 1024),
+        n => (n, 1),
+    };
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            anyhow::anyhow!("❌ Invalid padding size '{size}', expected e.g. 256, 1k or 4KiB")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pads_to_smallest_fitting_bucket() {
+        let buckets = DEFAULT_BUCKETS;
+        assert_eq!(pad(&[1; 32], &buckets).unwrap().len(), 256);
+        assert_eq!(pad(&[1; 248], &buckets).unwrap().len(), 256);
+        assert_eq!(pad(&[1; 249], &buckets).unwrap().len(), 1024);
+        assert_eq!(pad(&[1; 3000], &buckets).unwrap().len(), 4096);
+        assert_eq!(pad(&[1; 5000], &buckets).unwrap().len(), 8192);
+    }
+
+    #[test]
+    fn test_unpad_restores_data_ending_in_zeros() {
+        let data = [7, 0, 0, 0];
+        let padded = pad(&data, &[64]).unwrap();
+        assert!(is_padded(&padded));
+        assert_eq!(unpad(&padded).unwrap(), data);
+        assert!(unpad(&padded[..6]).is_err());
+    }
+
+    #[test]
+    fn test_raw_data_starting_with_the_magic_is_not_padded() {
+        let mut padded = pad(&[7; 8], &[64]).unwrap();
+        // Truncated before the declared length
+        assert!(!is_padded(&padded[..HEADER_LEN + 4]));
+        // Something other than zeros after the data
+        padded[40] = 1;
+        assert!(!is_padded(&padded));
+        assert!(unpad(&padded).is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("256").unwrap(), 256);
+        assert_eq!(parse_size("1k").unwrap(), 1024);
+        assert_eq!(parse_size("4KiB").unwrap(), 4096);
+        assert_eq!(parse_size("1MB").unwrap(), 1024 * 1024);
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("big").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use dialoguer::Password;
+
+/// Marks data sealed by `seal`, followed by a format version.
+const MAGIC: &[u8; 4] = b"ZPW\x01";
+/// Identifies the KDF in the header, so stronger ones can be added later.
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 4 + 1 + SALT_LEN;
+
+// Layout:
+//   MAGIC | KDF id (1) | memory in KiB (u32 BE) | passes (u32 BE) | lanes (1) | salt (16)
+//   | nonce (12) | ChaCha20-Poly1305(derived key, plaintext, aad = everything before the nonce)
+//
+// The KDF parameters travel with the data, so they can be raised later without breaking
+// what's already stored.
+
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub passes: u32,
+    pub lanes: u8,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB and 3 passes: slow enough to make guessing a passphrase expensive, while
+    /// sealing or opening still takes well under a second.
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 64 * 1024,
+            passes: 3,
+            lanes: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// The most `open` will spend on data it's given: four times the defaults. The parameters
+    /// come from the data itself, so without a ceiling stored data could make a reader allocate
+    /// gigabytes or spin for hours before the passphrase is even checked.
+    const CEILING_FACTOR: u32 = 4;
+
+    fn exceeds_ceiling(&self) -> bool {
+        let max = KdfParams::default();
+        self.memory_kib > max.memory_kib.saturating_mul(Self::CEILING_FACTOR)
+            || self.passes > max.passes.saturating_mul(Self::CEILING_FACTOR)
+            || self.lanes as u32 > max.lanes as u32 * Self::CEILING_FACTOR
+    }
+}
+
+/// Whether `data` was sealed with a passphrase by `seal`.
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Reads the passphrase from `file` (its first line) or prompts for it, twice if `confirm`.
+pub fn read_passphrase(
+    file: Option<&str>,
+    prompt: &str,
+    confirm: bool,
+) -> Result<String, anyhow::Error> {
+    if let Some(path) = file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read passphrase file {path}: {e}"))?;
+        return Ok(contents.lines().next().unwrap_or_default().to_string());
+    }
+
+    let mut input = Password::new().with_prompt(prompt);
+    if confirm {
+        input = input.with_confirmation("🔐 Repeat passphrase", "❌ Passphrases don't match");
+    }
+    input
+        .interact()
+        .map_err(|e| anyhow::anyhow!("Failed to read passphrase: {e}"))
+}
+
+/// Encrypts `plaintext` under a key derived from `passphrase`.
+pub fn seal(
+    plaintext: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>, anyhow::Error> {
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("❌ The passphrase must not be empty"));
+    }
+    let salt: [u8; SALT_LEN] = rand::random();
+
+    let mut sealed = MAGIC.to_vec();
+    sealed.push(KDF_ARGON2ID);
+    sealed.extend_from_slice(&params.memory_kib.to_be_bytes());
+    sealed.extend_from_slice(&params.passes.to_be_bytes());
+    sealed.push(params.lanes);
+    sealed.extend_from_slice(&salt);
+
+    let key = derive_key(passphrase, &salt, params)?;
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let ciphertext = ChaCha20Poly1305::new(&key)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &sealed,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt payload"))?;
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts data sealed by `seal`, using the KDF parameters from its header.
+pub fn open(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, anyhow::Error> {
+    if !is_sealed(sealed) {
+        return Err(anyhow::anyhow!("❌ Data is not passphrase-encrypted"));
+    }
+    if sealed.len() < HEADER_LEN + NONCE_LEN {
+        return Err(anyhow::anyhow!(
+            "❌ Encrypted payload is truncated or corrupt"
+        ));
+    }
+
+    let (header, rest) = sealed.split_at(HEADER_LEN);
+    let kdf = header[MAGIC.len()];
+    if kdf != KDF_ARGON2ID {
+        return Err(anyhow::anyhow!("❌ Unsupported passphrase KDF {kdf}"));
+    }
+    let u32_at =
+        |offset: usize| u32::from_be_bytes(header[offset..offset + 4].try_into().expect("4 bytes"));
+    let params = KdfParams {
+        memory_kib: u32_at(MAGIC.len() + 1),
+        passes: u32_at(MAGIC.len() + 5),
+        lanes: header[MAGIC.len() + 9],
+    };
+    if params.exceeds_ceiling() {
+        return Err(anyhow::anyhow!(
+            "❌ Passphrase KDF parameters are too costly ({} KiB, {} passes, {} lanes), refusing to derive the key",
+            params.memory_kib,
+            params.passes,
+            params.lanes
+        ));
+    }
+    let salt = &header[MAGIC.len() + 10..];
+
+    let key = derive_key(passphrase, salt, params)?;
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(&key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("❌ Wrong passphrase, or the payload was tampered with"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Key, anyhow::Error> {
+    let params = Params::new(
+        params.memory_kib,
+        params.passes,
+        params.lanes as u32,
+        Some(32),
+    )
+    .map_err(|e| anyhow::anyhow!("❌ Invalid Argon2id parameters: {e}"))?;
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("❌ Failed to derive key from passphrase: {e}"))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters keep the tests fast; the format is the same.
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        passes: 1,
+        lanes: 1,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let sealed = seal(b"for the lawyer", "correct horse", TEST_PARAMS).unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(open(&sealed, "correct horse").unwrap(), b"for the lawyer");
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        let sealed = seal(b"for the lawyer", "correct horse", TEST_PARAMS).unwrap();
+        assert!(open(&sealed, "battery staple").is_err());
+    }
+
+    #[test]
+    fn test_header_tampering_fails() {
+        let mut sealed = seal(b"for the lawyer", "correct horse", TEST_PARAMS).unwrap();
+        // Raising the recorded passes changes the derived key and the authenticated header.
+        sealed[MAGIC.len() + 8] += 1;
+        assert!(open(&sealed, "correct horse").is_err());
+        assert!(open(&sealed[..HEADER_LEN], "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_costly_header_params_are_refused() {
+        let mut sealed = seal(b"for the lawyer", "correct horse", TEST_PARAMS).unwrap();
+        sealed[MAGIC.len() + 1..MAGIC.len() + 5].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = open(&sealed, "correct horse").unwrap_err();
+        assert!(err.to_string().contains("too costly"), "{err}");
+
+        assert!(!KdfParams::default().exceeds_ceiling());
+    }
+
+    #[test]
+    fn test_empty_passphrase_is_refused() {
+        assert!(seal(b"x", "", TEST_PARAMS).is_err());
+    }
+}
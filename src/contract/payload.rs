@@ -0,0 +1,226 @@
+use flate2::{Compression as GzLevel, read::GzDecoder, write::GzEncoder};
+use sha2::{Digest, Sha256};
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+/// Marks key data packed by `pack`, followed by a format version.
+const MAGIC: &[u8; 4] = b"ZPL\x01";
+const DIGEST_LEN: usize = 32;
+
+// Layout:
+//   MAGIC | compression (1) | content type length (1) | content type
+//   | filename length (1) | filename | plaintext length (u64 BE) | SHA-256 of plaintext (32)
+//   | body (the plaintext, compressed as stated)
+//
+// Anything without the magic is legacy data: raw bytes the user encoded themselves.
+
+/// How the body of a packed payload is compressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None = 0,
+    Gzip = 1,
+}
+
+/// A file stored under a key, with what's needed to restore it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payload {
+    pub content_type: String,
+    pub filename: Option<String>,
+    pub compression: Compression,
+    pub data: Vec<u8>,
+}
+
+impl Payload {
+    /// Wraps the contents of `path` (or stdin for `-`), guessing the content type from the
+    /// file extension unless one is given.
+    pub fn from_file(
+        path: &str,
+        content_type: Option<&str>,
+        compression: Compression,
+    ) -> Result<Self, anyhow::Error> {
+        let data = if path == "-" {
+            let mut data = Vec::new();
+            std::io::stdin().read_to_end(&mut data)?;
+            data
+        } else {
+            std::fs::read(path).map_err(|e| anyhow::anyhow!("❌ Failed to read {path}: {e}"))?
+        };
+        let filename = (path != "-")
+            .then(|| Path::new(path).file_name())
+            .flatten()
+            .map(|name| name.to_string_lossy().into_owned());
+
+        Ok(Payload {
+            content_type: content_type
+                .map(str::to_string)
+                .unwrap_or_else(|| guess_content_type(path).to_string()),
+            filename,
+            compression,
+            data,
+        })
+    }
+
+    /// Whether the data is meant to be read as text.
+    pub fn is_text(&self) -> bool {
+        self.content_type.starts_with("text/") || self.content_type == "application/json"
+    }
+}
+
+/// Whether key data is a payload packed by `pack`.
+pub fn is_packed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn pack(payload: &Payload) -> Result<Vec<u8>, anyhow::Error> {
+    let filename = payload.filename.as_deref().unwrap_or_default();
+    if payload.content_type.len() > u8::MAX as usize || filename.len() > u8::MAX as usize {
+        return Err(anyhow::anyhow!(
+            "❌ Content type and filename must be at most {} bytes",
+            u8::MAX
+        ));
+    }
+
+    let mut packed = MAGIC.to_vec();
+    packed.push(payload.compression as u8);
+    packed.push(payload.content_type.len() as u8);
+    packed.extend_from_slice(payload.content_type.as_bytes());
+    packed.push(filename.len() as u8);
+    packed.extend_from_slice(filename.as_bytes());
+    packed.extend_from_slice(&(payload.data.len() as u64).to_be_bytes());
+    packed.extend_from_slice(&Sha256::digest(&payload.data));
+
+    match payload.compression {
+        Compression::None => packed.extend_from_slice(&payload.data),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(packed, GzLevel::best());
+            encoder.write_all(&payload.data)?;
+            packed = encoder.finish()?;
+        }
+    }
+    Ok(packed)
+}
+
+/// Unpacks a payload, checking its length and digest.
+pub fn unpack(packed: &[u8]) -> Result<Payload, anyhow::Error> {
+    let corrupt = || anyhow::anyhow!("❌ Payload is truncated or corrupt");
+    if !is_packed(packed) {
+        return Err(anyhow::anyhow!("❌ Data is not a packed payload"));
+    }
+
+    let mut rest = &packed[MAGIC.len()..];
+    let mut take = |len: usize| -> Result<&[u8], anyhow::Error> {
+        let (head, tail) = rest.split_at_checked(len).ok_or_else(corrupt)?;
+        rest = tail;
+        Ok(head)
+    };
+
+    let compression = match take(1)?[0] {
+        0 => Compression::None,
+        1 => Compression::Gzip,
+        other => return Err(anyhow::anyhow!("❌ Unknown payload compression {other}")),
+    };
+    let content_type_len = take(1)?[0] as usize;
+    let content_type =
+        String::from_utf8(take(content_type_len)?.to_vec()).map_err(|_| corrupt())?;
+    let filename_len = take(1)?[0] as usize;
+    let filename = String::from_utf8(take(filename_len)?.to_vec()).map_err(|_| corrupt())?;
+    let length = u64::from_be_bytes(take(8)?.try_into().expect("8 bytes"));
+    let digest = take(DIGEST_LEN)?.to_vec();
+    let body = rest;
+
+    let data = match compression {
+        Compression::None => body.to_vec(),
+        Compression::Gzip => {
+            let mut data = Vec::new();
+            // Never inflate past the declared length, whatever the body claims.
+            GzDecoder::new(body)
+                .take(length.saturating_add(1))
+                .read_to_end(&mut data)
+                .map_err(|_| corrupt())?;
+            data
+        }
+    };
+    if data.len() as u64 != length || Sha256::digest(&data).as_slice() != digest {
+        return Err(anyhow::anyhow!(
+            "❌ Payload failed its integrity check (length or SHA-256 mismatch)"
+        ));
+    }
+
+    Ok(Payload {
+        content_type,
+        filename: (!filename.is_empty()).then_some(filename),
+        compression,
+        data,
+    })
+}
+
+fn guess_content_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("txt" | "md") => "text/plain",
+        Some("json") => "application/json",
+        Some("pdf") => "application/pdf",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("zip") => "application/zip",
+        Some("gz" | "tgz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(compression: Compression) -> Payload {
+        Payload {
+            content_type: "text/plain".to_string(),
+            filename: Some("notes.txt".to_string()),
+            compression,
+            data: b"remember remember the fifth of november ".repeat(20),
+        }
+    }
+
+    #[test]
+    fn test_round_trips_with_and_without_compression() {
+        for compression in [Compression::None, Compression::Gzip] {
+            let payload = sample(compression);
+            let packed = pack(&payload).unwrap();
+            assert!(is_packed(&packed));
+            assert_eq!(unpack(&packed).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn test_gzip_shrinks_repetitive_data() {
+        let plain = pack(&sample(Compression::None)).unwrap();
+        let gzipped = pack(&sample(Compression::Gzip)).unwrap();
+        assert!(gzipped.len() < plain.len());
+    }
+
+    #[test]
+    fn test_corruption_is_detected() {
+        let mut packed = pack(&sample(Compression::None)).unwrap();
+        let last = packed.len() - 1;
+        packed[last] ^= 1;
+        assert!(unpack(&packed).is_err());
+        assert!(unpack(&packed[..20]).is_err());
+    }
+
+    #[test]
+    fn test_legacy_data_is_not_packed() {
+        assert!(!is_packed(b"Hello, Zaph!"));
+    }
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type("a/notes.TXT"), "text/plain");
+        assert_eq!(guess_content_type("wallet.json"), "application/json");
+        assert_eq!(guess_content_type("-"), "application/octet-stream");
+    }
+}
@@ -1,16 +1,20 @@
 use crate::contract::types::{ContractSpecs, GasSpecs};
+use crate::contract::{network, transaction};
 use dialoguer::Confirm;
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 use web3::{
     contract::{Contract, Options},
-    signing::{Key, SecretKeyRef},
     transports::Http,
     types::{Address, H256, U64, U256},
 };
 
+/// How long to wait for the ping to be mined before giving up on it. The daemon runs unattended,
+/// so a transaction stuck in the mempool must not hold its ping slot forever.
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(300);
+
 pub async fn ping_key_on_chain(
     contract_specs: &mut ContractSpecs,
-    key_id: &str,
+    key_hash: H256,
     owner_address: &str,
     yes: bool,
     gas_specs: GasSpecs,
@@ -18,21 +22,16 @@ pub async fn ping_key_on_chain(
 ) -> Result<H256, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
     let web3 = web3::Web3::new(http);
+    network::verify_chain_id(&web3, &contract_specs.ctx).await?;
 
     let contract_address = Address::from_str(&contract_specs.contract_addr)
         .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
 
-    let sk = if let Some(sec) = contract_specs.get_private_key() {
-        sec
-    } else {
-        contract_specs.load_private_key_if_missing()?
-    };
-    let wallet = SecretKeyRef::new(sk);
+    let from = contract_specs.signer_address()?;
 
     let abi_json = include_str!("../../abi/Zaphenath.json");
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_id.as_bytes()).as_slice());
     let owner = Address::from_str(owner_address)?;
 
     let call_params = (key_hash, owner);
@@ -43,7 +42,7 @@ pub async fn ping_key_on_chain(
         None
     } else {
         let est: U256 = contract
-            .estimate_gas("ping", call_params, wallet.address(), Options::default())
+            .estimate_gas("ping", call_params, from, Options::default())
             .await?;
 
         let gas_with_buffer = if let Some(buffer) = gas_specs.gas_buffer {
@@ -75,18 +74,32 @@ pub async fn ping_key_on_chain(
         ..Options::default()
     };
 
-    let tx_hash = contract
-        .signed_call("ping", call_params, options, wallet)
-        .await?;
+    let tx_hash = transaction::send_transaction(
+        &web3,
+        &contract,
+        contract_specs,
+        "ping",
+        call_params,
+        options,
+    )
+    .await?;
 
     println!("⏳ Waiting for transaction {:?} to be mined...", tx_hash);
 
+    let deadline = tokio::time::Instant::now() + RECEIPT_TIMEOUT;
     let receipt = loop {
         match web3.eth().transaction_receipt(tx_hash).await {
             Ok(Some(receipt)) => break receipt,
+            Ok(None) if tokio::time::Instant::now() >= deadline => {
+                return Err(anyhow::anyhow!(
+                    "Transaction {:?} not mined after {}s, it may still be pending",
+                    tx_hash,
+                    RECEIPT_TIMEOUT.as_secs()
+                ));
+            }
             Ok(None) => {
                 // Transaction not yet mined, wait and retry
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                tokio::time::sleep(Duration::from_secs(1)).await;
             }
             Err(e) => {
                 return Err(anyhow::anyhow!(
@@ -100,6 +113,9 @@ pub async fn ping_key_on_chain(
     if let Some(status) = receipt.status {
         if status == U64::one() {
             println!("📡 Pinged on-chain. Tx hash: {:?}", tx_hash);
+            if let Some(link) = contract_specs.ctx.explorer_link(&tx_hash) {
+                println!("🔗 {link}");
+            }
             Ok(tx_hash)
         } else {
             Err(anyhow::anyhow!(
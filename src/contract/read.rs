@@ -1,6 +1,5 @@
-use crate::contract::types::ContractSpecs;
+use crate::contract::types::{ContractSpecs, NetworkContext};
 use std::str::FromStr;
-use web3::signing::{Key, SecretKeyRef};
 use web3::{
     contract::{Contract, Options},
     transports::Http,
@@ -9,7 +8,7 @@ use web3::{
 
 pub async fn read_key_on_chain(
     contract_specs: &mut ContractSpecs,
-    key_id: &str,
+    key_hash: H256,
     owner_address: &str,
 ) -> Result<Bytes, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
@@ -21,22 +20,8 @@ pub async fn read_key_on_chain(
     let abi_json = include_str!("../../abi/Zaphenath.json");
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    // 🧠 Derive owner address from private key
-    let sk = match contract_specs.get_private_key() {
-        Some(sec) => sec,
-        None => match contract_specs.load_private_key_if_missing() {
-            Ok(sec) => sec,
-            Err(e) => {
-                let e = anyhow::anyhow!("Failed to load private key: {:?}", e);
-                return Err(e);
-            }
-        },
-    };
-
-    let wallet = SecretKeyRef::new(sk);
-    let caller_addr: Address = wallet.address();
+    let caller_addr: Address = contract_specs.signer_address()?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_id.as_bytes()).as_slice());
     let owner = Address::from_str(owner_address)?;
 
     let options = Options {
@@ -55,3 +40,32 @@ pub async fn read_key_on_chain(
 
     Ok(result)
 }
+
+/// Asks the contract's pure `getFullKey` for the storage key of `key_hash` under `owner`.
+pub async fn full_key_on_chain(
+    ctx: &NetworkContext,
+    contract_addr: &str,
+    key_hash: H256,
+    owner: Address,
+) -> Result<H256, anyhow::Error> {
+    let http = Http::new(&ctx.rpc_url)?;
+    let web3 = web3::Web3::new(http);
+
+    let contract_address = Address::from_str(contract_addr)
+        .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
+
+    let abi_json = include_str!("../../abi/Zaphenath.json");
+    let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
+
+    let result: H256 = contract
+        .query(
+            "getFullKey",
+            (key_hash, owner),
+            None,
+            Options::default(),
+            None,
+        )
+        .await?;
+
+    Ok(result)
+}
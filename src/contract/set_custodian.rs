@@ -1,9 +1,9 @@
 use crate::contract::types::{ContractSpecs, CustodianData, GasSpecs}; // Added CustodianData
+use crate::contract::{network, transaction};
 use dialoguer::Confirm;
 use std::str::FromStr;
 use web3::Web3;
 use web3::contract::{Contract, Options};
-use web3::signing::{Key, SecretKeyRef};
 use web3::transports::Http;
 use web3::types::{Address, H256, U64, U256}; // Import Web3
 
@@ -11,7 +11,7 @@ use web3::types::{Address, H256, U64, U256}; // Import Web3
 
 pub async fn set_custodian_on_chain(
     contract_specs: &mut ContractSpecs,
-    key_id: &str,
+    key_hash: H256,
     owner_address: &str,            // The owner of the key
     custodian_data: &CustodianData, // Use the CustodianData struct
     yes: bool,
@@ -20,21 +20,17 @@ pub async fn set_custodian_on_chain(
 ) -> Result<H256, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
     let web3 = Web3::new(http); // Use the imported Web3
+    network::verify_chain_id(&web3, &contract_specs.ctx).await?;
 
     let contract_address = Address::from_str(&contract_specs.contract_addr)
         .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
 
     // Ensure private key is loaded for signing
-    let sk = match contract_specs.get_private_key() {
-        Some(sec) => sec,
-        None => contract_specs.load_private_key_if_missing()?,
-    };
-    let wallet = SecretKeyRef::new(sk);
+    let from = contract_specs.signer_address()?;
 
     let abi_json = include_str!("../../abi/Zaphenath.json"); // Adjust path if needed
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_id.as_bytes()).as_slice());
     let owner = Address::from_str(owner_address)?;
     let user = Address::from_str(&custodian_data.address)?;
     let role_u8: u8 = custodian_data.role.into(); // Convert Role enum to u8
@@ -47,12 +43,7 @@ pub async fn set_custodian_on_chain(
         None // Auto-estimate gas without buffering if --yes and no buffer specified
     } else {
         let est: U256 = contract
-            .estimate_gas(
-                "setCustodian",
-                call_params,
-                wallet.address(),
-                Options::default(),
-            )
+            .estimate_gas("setCustodian", call_params, from, Options::default())
             .await?;
 
         let gas_with_buffer = if let Some(buffer) = gas_specs.gas_buffer {
@@ -84,9 +75,15 @@ pub async fn set_custodian_on_chain(
         ..Options::default()
     };
 
-    let tx_hash = contract
-        .signed_call("setCustodian", call_params, options, wallet)
-        .await?;
+    let tx_hash = transaction::send_transaction(
+        &web3,
+        &contract,
+        contract_specs,
+        "setCustodian",
+        call_params,
+        options,
+    )
+    .await?;
 
     println!("⏳ Waiting for transaction {:?} to be mined...", tx_hash);
 
@@ -109,9 +106,12 @@ pub async fn set_custodian_on_chain(
     if let Some(status) = receipt.status {
         if status == U64::one() {
             println!(
-                "✅ Custodian '{:#x}' set for key '{}' on-chain. Tx hash: {:?}",
-                user, key_id, tx_hash
+                "✅ Custodian '{:#x}' set for key {:#x} on-chain. Tx hash: {:?}",
+                user, key_hash, tx_hash
             );
+            if let Some(link) = contract_specs.ctx.explorer_link(&tx_hash) {
+                println!("🔗 {link}");
+            }
             Ok(tx_hash)
         } else {
             Err(anyhow::anyhow!(
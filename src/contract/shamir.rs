@@ -0,0 +1,253 @@
+use super::ecies;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use secp256k1::PublicKey;
+use sha2::Sha256;
+use sharks::{Share, Sharks};
+
+/// Marks key data holding one sealed share per custodian, followed by a format version.
+const SPLIT_MAGIC: &[u8; 4] = b"ZSS\x01";
+/// Marks a single decrypted share, as passed to `zaph recover combine`.
+const SHARE_MAGIC: &[u8; 4] = b"ZSH\x01";
+const CHECK_KEY_LEN: usize = 32;
+const TAG_LEN: usize = 32;
+
+// Key data layout:
+//   SPLIT_MAGIC | threshold (1) | share count (1) | per share: length (u32 BE) | ECIES envelope
+//
+// Each envelope is sealed to one custodian and holds:
+//   SHARE_MAGIC | threshold (1) | tag (32) | Shamir share (x, then y bytes)
+//
+// What is split is `secret | check key`, where the check key is random, and the tag is
+// HMAC-SHA256(check key, secret). It lets `combine` tell a correct recovery from garbage made of
+// mismatched shares, yet a single custodian can't test guesses of the secret against it: the key
+// is only known once enough shares are combined.
+
+/// Whether key data holds split shares made by `split`.
+pub fn is_split(data: &[u8]) -> bool {
+    data.starts_with(SPLIT_MAGIC)
+}
+
+/// Splits `secret` into one share per recipient, any `threshold` of which rebuild it, and
+/// seals each share to its recipient.
+pub fn split(
+    secret: &[u8],
+    threshold: u8,
+    recipients: &[PublicKey],
+) -> Result<Vec<u8>, anyhow::Error> {
+    if threshold == 0 || threshold as usize > recipients.len() {
+        return Err(anyhow::anyhow!(
+            "❌ Threshold must be between 1 and the number of recipients ({})",
+            recipients.len()
+        ));
+    }
+    if recipients.len() > u8::MAX as usize {
+        return Err(anyhow::anyhow!(
+            "❌ At most {} shares are supported",
+            u8::MAX
+        ));
+    }
+
+    let mut check_key = [0u8; CHECK_KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut check_key);
+    let tag = check_tag(&check_key, secret);
+    let shares = Sharks(threshold).dealer(&[secret, &check_key[..]].concat());
+
+    let mut data = SPLIT_MAGIC.to_vec();
+    data.push(threshold);
+    data.push(recipients.len() as u8);
+    for (share, recipient) in shares.zip(recipients) {
+        let mut plaintext = SHARE_MAGIC.to_vec();
+        plaintext.push(threshold);
+        plaintext.extend_from_slice(&tag);
+        plaintext.extend_from_slice(&Vec::from(&share));
+
+        let sealed = ecies::seal(&plaintext, std::slice::from_ref(recipient))?;
+        data.extend_from_slice(&(sealed.len() as u32).to_be_bytes());
+        data.extend_from_slice(&sealed);
+    }
+    Ok(data)
+}
+
+fn check_tag(check_key: &[u8], secret: &[u8]) -> [u8; TAG_LEN] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(check_key).expect("HMAC accepts any key length");
+    mac.update(secret);
+    mac.finalize().into_bytes().into()
+}
+
+/// Finds and decrypts the share sealed to `secret_key`, returning it as passed to `combine`,
+/// along with the threshold.
+pub fn open_share(data: &[u8], secret_key: &[u8; 32]) -> Result<(Vec<u8>, u8), anyhow::Error> {
+    let corrupt = || anyhow::anyhow!("❌ Split key data is truncated or corrupt");
+    if !is_split(data) {
+        return Err(anyhow::anyhow!("❌ Data does not hold split shares"));
+    }
+
+    let threshold = *data.get(SPLIT_MAGIC.len()).ok_or_else(corrupt)?;
+    let count = *data.get(SPLIT_MAGIC.len() + 1).ok_or_else(corrupt)?;
+    let mut rest = &data[SPLIT_MAGIC.len() + 2..];
+    for _ in 0..count {
+        let len = rest
+            .get(..4)
+            .map(|len| u32::from_be_bytes(len.try_into().expect("4 bytes")) as usize)
+            .ok_or_else(corrupt)?;
+        let sealed = rest.get(4..4 + len).ok_or_else(corrupt)?;
+        rest = &rest[4 + len..];
+
+        if let Ok(share) = ecies::open(sealed, secret_key) {
+            return Ok((share, threshold));
+        }
+    }
+    Err(anyhow::anyhow!(
+        "❌ None of the shares is encrypted to this key"
+    ))
+}
+
+/// Rebuilds the secret from decrypted shares, checking it against the tag they carry.
+pub fn combine(shares: &[Vec<u8>]) -> Result<Vec<u8>, anyhow::Error> {
+    let header_len = SHARE_MAGIC.len() + 1 + TAG_LEN;
+    let mut threshold = None;
+    let mut tag = None;
+    let mut parsed: Vec<Share> = Vec::new();
+
+    for share in shares {
+        if !share.starts_with(SHARE_MAGIC) || share.len() <= header_len + 1 {
+            return Err(anyhow::anyhow!("❌ Not a zaph secret share"));
+        }
+        let (header, body) = share.split_at(header_len);
+        let share_threshold = header[SHARE_MAGIC.len()];
+        let share_tag = &header[SHARE_MAGIC.len() + 1..];
+        if *threshold.get_or_insert(share_threshold) != share_threshold
+            || *tag.get_or_insert(share_tag) != share_tag
+        {
+            return Err(anyhow::anyhow!("❌ Shares belong to different secrets"));
+        }
+
+        let share = Share::try_from(body).map_err(|e| anyhow::anyhow!("❌ Invalid share: {e}"))?;
+        // The same share given twice would break interpolation, count it once.
+        if parsed.iter().all(|known| known.x != share.x) {
+            parsed.push(share);
+        }
+    }
+
+    let (Some(threshold), Some(tag)) = (threshold, tag) else {
+        return Err(anyhow::anyhow!("❌ No shares given"));
+    };
+    if parsed.len() < threshold as usize {
+        return Err(anyhow::anyhow!(
+            "❌ {} distinct share(s) given, but {threshold} are needed",
+            parsed.len()
+        ));
+    }
+
+    let mut secret = Sharks(threshold)
+        .recover(&parsed)
+        .map_err(|e| anyhow::anyhow!("❌ Failed to combine shares: {e}"))?;
+    let mismatch =
+        || anyhow::anyhow!("❌ Combined secret doesn't match its checksum, a share is corrupt");
+    let secret_len = secret
+        .len()
+        .checked_sub(CHECK_KEY_LEN)
+        .ok_or_else(mismatch)?;
+    let check_key = secret.split_off(secret_len);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&check_key).expect("HMAC accepts any key length");
+    mac.update(&secret);
+    mac.verify_slice(tag).map_err(|_| mismatch())?;
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custodians(n: u8) -> Vec<([u8; 32], PublicKey)> {
+        (1..=n)
+            .map(|i| {
+                let secret = [i; 32];
+                (secret, ecies::public_key(&secret).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_any_k_of_n_shares_rebuild_the_secret() {
+        let custodians = custodians(3);
+        let public: Vec<_> = custodians.iter().map(|(_, public)| *public).collect();
+        let data = split(b"seed words", 2, &public).unwrap();
+        assert!(is_split(&data));
+
+        let shares: Vec<Vec<u8>> = custodians
+            .iter()
+            .map(|(secret, _)| open_share(&data, secret).unwrap().0)
+            .collect();
+        assert_eq!(combine(&shares[..2]).unwrap(), b"seed words");
+        assert_eq!(combine(&shares[1..]).unwrap(), b"seed words");
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[2].clone()]).unwrap(),
+            b"seed words"
+        );
+    }
+
+    #[test]
+    fn test_too_few_or_repeated_shares_fail() {
+        let custodians = custodians(3);
+        let public: Vec<_> = custodians.iter().map(|(_, public)| *public).collect();
+        let data = split(b"seed words", 2, &public).unwrap();
+        let (share, threshold) = open_share(&data, &custodians[0].0).unwrap();
+
+        assert_eq!(threshold, 2);
+        assert!(combine(std::slice::from_ref(&share)).is_err());
+        assert!(combine(&[share.clone(), share]).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_shares_are_rejected() {
+        let custodians = custodians(2);
+        let public: Vec<_> = custodians.iter().map(|(_, public)| *public).collect();
+        let first = split(b"one secret", 2, &public).unwrap();
+        let second = split(b"another one", 2, &public).unwrap();
+
+        let shares = vec![
+            open_share(&first, &custodians[0].0).unwrap().0,
+            open_share(&second, &custodians[1].0).unwrap().0,
+        ];
+        assert!(combine(&shares).is_err());
+    }
+
+    #[test]
+    fn test_shares_reveal_no_check_on_the_secret_alone() {
+        use sha2::{Digest, Sha256};
+
+        let custodians = custodians(2);
+        let public: Vec<_> = custodians.iter().map(|(_, public)| *public).collect();
+        let first = open_share(&split(b"1234", 2, &public).unwrap(), &custodians[0].0)
+            .unwrap()
+            .0;
+        let second = open_share(&split(b"1234", 2, &public).unwrap(), &custodians[0].0)
+            .unwrap()
+            .0;
+
+        let tag = |share: &[u8]| share[SHARE_MAGIC.len() + 1..][..TAG_LEN].to_vec();
+        assert_ne!(tag(&first), Sha256::digest(b"1234").to_vec());
+        // The tag is keyed by a fresh random key, so the same secret gets a different one
+        assert_ne!(tag(&first), tag(&second));
+    }
+
+    #[test]
+    fn test_non_recipient_has_no_share() {
+        let custodians = custodians(2);
+        let public: Vec<_> = custodians.iter().map(|(_, public)| *public).collect();
+        let data = split(b"seed words", 2, &public).unwrap();
+        assert!(open_share(&data, &[9; 32]).is_err());
+    }
+
+    #[test]
+    fn test_threshold_must_fit_recipients() {
+        let public: Vec<_> = custodians(2)
+            .into_iter()
+            .map(|(_, public)| public)
+            .collect();
+        assert!(split(b"x", 3, &public).is_err());
+        assert!(split(b"x", 0, &public).is_err());
+    }
+}
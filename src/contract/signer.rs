@@ -0,0 +1,395 @@
+use super::{keystore, mnemonic};
+use futures::{FutureExt, future::BoxFuture};
+use rlp::RlpStream;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+use web3::{
+    signing::{Key, SecretKey, SecretKeyRef, Signature, hash_message, keccak256},
+    types::{Address, Bytes, U256},
+};
+
+/// An unsigned legacy (EIP-155) transaction, in the shape Clef's `account_signTransaction` expects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionRequest {
+    pub from: Address,
+    pub to: Address,
+    pub gas: U256,
+    pub gas_price: U256,
+    pub value: U256,
+    pub nonce: U256,
+    pub data: Bytes,
+    pub chain_id: U256,
+}
+
+/// Something that can sign on behalf of a single account.
+/// Contract calls only talk to keys through this trait, so new key sources plug in here.
+pub trait Signer: Send + Sync + fmt::Debug {
+    fn address(&self) -> Address;
+
+    /// Signs `tx`, returning the raw transaction ready to broadcast.
+    fn sign_transaction<'a>(
+        &'a self,
+        tx: &'a TransactionRequest,
+    ) -> BoxFuture<'a, Result<Bytes, anyhow::Error>>;
+
+    /// Signs `message` as an EIP-191 personal message, returning `r || s || v` with `v` in {27, 28}.
+    fn sign_message<'a>(
+        &'a self,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, anyhow::Error>>;
+}
+
+/// A private key held in memory, read from a hex file, a keystore, the mnemonic or the environment.
+pub struct LocalKeySigner {
+    key: SecretKey,
+}
+
+impl LocalKeySigner {
+    pub fn new(key: SecretKey) -> Self {
+        LocalKeySigner { key }
+    }
+
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.key
+    }
+
+    /// Reads a hex private key file, or an encrypted v3 keystore (a passphrase is requested).
+    pub fn from_file(path: &str) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)?;
+        if keystore::is_keystore(&contents) {
+            let passphrase = keystore::read_passphrase(path)?;
+            return Ok(Self::new(keystore::decrypt(path, &passphrase)?));
+        }
+        parse_hex_key(&contents)
+            .map(Self::new)
+            .map_err(|_| anyhow::anyhow!("Invalid private key format from path: {}", path))
+    }
+
+    /// Reads a hex private key from the environment variable `var`.
+    pub fn from_env(var: &str) -> Result<Self, anyhow::Error> {
+        let value = std::env::var(var)
+            .map_err(|_| anyhow::anyhow!("Environment variable {var} is not set"))?;
+        parse_hex_key(&value)
+            .map(Self::new)
+            .map_err(|_| anyhow::anyhow!("Invalid private key format in {var}"))
+    }
+
+    /// Derives the key at `hd_path` from the mnemonic (see `contract::mnemonic`).
+    pub fn from_mnemonic(hd_path: &str) -> Result<Self, anyhow::Error> {
+        let phrase = mnemonic::read_mnemonic()?;
+        Ok(Self::new(mnemonic::derive_secret_key(&phrase, hd_path)?))
+    }
+}
+
+/// Parses a hex private key, with or without `0x` and surrounding whitespace.
+pub fn parse_hex_key(s: &str) -> Result<SecretKey, anyhow::Error> {
+    Ok(SecretKey::from_str(s.trim().trim_start_matches("0x"))?)
+}
+
+impl fmt::Debug for LocalKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKeySigner")
+            .field("address", &self.address())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer for LocalKeySigner {
+    fn address(&self) -> Address {
+        SecretKeyRef::new(&self.key).address()
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        tx: &'a TransactionRequest,
+    ) -> BoxFuture<'a, Result<Bytes, anyhow::Error>> {
+        futures::future::ready(sign_legacy_transaction(&self.key, tx)).boxed()
+    }
+
+    fn sign_message<'a>(
+        &'a self,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, anyhow::Error>> {
+        let result = SecretKeyRef::new(&self.key)
+            .sign(hash_message(message).as_bytes(), None)
+            .map(|signature| signature_bytes(&signature))
+            .map_err(|e| anyhow::anyhow!("Failed to sign message: {e}"));
+        futures::future::ready(result).boxed()
+    }
+}
+
+fn sign_legacy_transaction(
+    key: &SecretKey,
+    tx: &TransactionRequest,
+) -> Result<Bytes, anyhow::Error> {
+    let chain_id = tx.chain_id.as_u64();
+    let hash = keccak256(&encode_legacy(tx, chain_id, None));
+    let signature = SecretKeyRef::new(key)
+        .sign(&hash, Some(chain_id))
+        .map_err(|e| anyhow::anyhow!("Failed to sign transaction: {e}"))?;
+    Ok(Bytes(encode_legacy(tx, chain_id, Some(&signature))))
+}
+
+/// RLP-encodes a legacy transaction; unsigned encodings carry the chain ID for EIP-155.
+fn encode_legacy(tx: &TransactionRequest, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
+    let mut stream = RlpStream::new();
+    stream.begin_list(9);
+    stream.append(&tx.nonce);
+    stream.append(&tx.gas_price);
+    stream.append(&tx.gas);
+    stream.append(&tx.to);
+    stream.append(&tx.value);
+    stream.append(&tx.data.0);
+    match signature {
+        Some(signature) => {
+            stream.append(&signature.v);
+            stream.append(&U256::from_big_endian(signature.r.as_bytes()));
+            stream.append(&U256::from_big_endian(signature.s.as_bytes()));
+        }
+        None => {
+            stream.append(&chain_id);
+            stream.append(&0u8);
+            stream.append(&0u8);
+        }
+    }
+    stream.out().to_vec()
+}
+
+fn signature_bytes(signature: &Signature) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(65);
+    bytes.extend_from_slice(signature.r.as_bytes());
+    bytes.extend_from_slice(signature.s.as_bytes());
+    bytes.push(signature.v as u8);
+    bytes
+}
+
+/// Delegates signing to a user-specified program, run as `<program> <operation>`:
+///
+/// - `address`: print the signing account's address.
+/// - `sign-transaction`: read a `TransactionRequest` as JSON on stdin, print the signed raw tx as hex.
+/// - `sign-message`: read the message as hex on stdin, print the 65-byte signature as hex.
+#[derive(Debug)]
+pub struct CommandSigner {
+    program: String,
+    address: Address,
+}
+
+impl CommandSigner {
+    /// Asks `program` for its address up front, so a broken signer fails before anything is built.
+    pub fn new(program: &str) -> Result<Self, anyhow::Error> {
+        let output = run_command(program, "address", "")?;
+        let address = Address::from_str(output.trim_start_matches("0x"))
+            .map_err(|_| anyhow::anyhow!("Signer command {program} printed an invalid address"))?;
+        Ok(CommandSigner {
+            program: program.to_string(),
+            address,
+        })
+    }
+}
+
+fn run_command(program: &str, operation: &str, input: &str) -> Result<String, anyhow::Error> {
+    let mut child = Command::new(program)
+        .arg(operation)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run signer command {program}: {e}"))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Signer command {program} {operation} failed with {}",
+            output.status
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Runs the signer program on the blocking pool, so a slow program doesn't hold up other tasks
+/// on the runtime, e.g. the daemon's concurrent pings.
+async fn run_command_off_runtime(
+    program: &str,
+    operation: &'static str,
+    input: String,
+) -> Result<String, anyhow::Error> {
+    let program = program.to_string();
+    tokio::task::spawn_blocking(move || run_command(&program, operation, &input))
+        .await
+        .map_err(|e| anyhow::anyhow!("Signer command task failed: {e}"))?
+}
+
+fn decode_hex_output(program: &str, output: &str) -> Result<Vec<u8>, anyhow::Error> {
+    hex::decode(output.trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("Signer command {program} printed invalid hex"))
+}
+
+impl Signer for CommandSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        tx: &'a TransactionRequest,
+    ) -> BoxFuture<'a, Result<Bytes, anyhow::Error>> {
+        async move {
+            let output = run_command_off_runtime(
+                &self.program,
+                "sign-transaction",
+                serde_json::to_string(tx)?,
+            )
+            .await?;
+            Ok(Bytes(decode_hex_output(&self.program, &output)?))
+        }
+        .boxed()
+    }
+
+    fn sign_message<'a>(
+        &'a self,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, anyhow::Error>> {
+        async move {
+            let output =
+                run_command_off_runtime(&self.program, "sign-message", hex::encode(message))
+                    .await?;
+            decode_hex_output(&self.program, &output)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web3::{transports::Http, types::TransactionParameters};
+
+    fn sample_tx(from: Address) -> TransactionRequest {
+        TransactionRequest {
+            from,
+            to: Address::from_low_u64_be(0xbeef),
+            gas: U256::from(60_000),
+            gas_price: U256::from(2_000_000_000u64),
+            value: U256::zero(),
+            nonce: U256::from(3),
+            data: Bytes(vec![0xde, 0xad]),
+            chain_id: U256::from(31337),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_signer_matches_web3_signing() {
+        let key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let signer = LocalKeySigner::new(key);
+        let tx = sample_tx(signer.address());
+
+        // With every field set, web3 signs offline; the transport is never used.
+        let web3 = web3::Web3::new(Http::new("http://127.0.0.1:1").unwrap());
+        let expected = web3
+            .accounts()
+            .sign_transaction(
+                TransactionParameters {
+                    nonce: Some(tx.nonce),
+                    to: Some(tx.to),
+                    gas: tx.gas,
+                    gas_price: Some(tx.gas_price),
+                    value: tx.value,
+                    data: tx.data.clone(),
+                    chain_id: Some(31337),
+                    ..Default::default()
+                },
+                &key,
+            )
+            .await
+            .unwrap();
+
+        let raw = signer.sign_transaction(&tx).await.unwrap();
+        assert_eq!(raw, expected.raw_transaction);
+    }
+
+    #[tokio::test]
+    async fn test_local_signer_message_recovers_to_address() {
+        let signer = LocalKeySigner::new(SecretKey::from_slice(&[0x42; 32]).unwrap());
+        let signature = signer.sign_message(b"zaphenath").await.unwrap();
+
+        let recovered = web3::signing::recover(
+            hash_message(b"zaphenath").as_bytes(),
+            &signature[..64],
+            signature[64] as i32 - 27,
+        )
+        .unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+
+    #[test]
+    fn test_env_signer_reads_hex_key() {
+        let var = "ZAPH_TEST_SIGNER_ENV_KEY";
+        // SAFETY: the variable name is unique to this test.
+        unsafe { std::env::set_var(var, format!("0x{}", "42".repeat(32))) };
+        let signer = LocalKeySigner::from_env(var).unwrap();
+        let expected = LocalKeySigner::new(SecretKey::from_slice(&[0x42; 32]).unwrap());
+        assert_eq!(signer.address(), expected.address());
+        assert!(LocalKeySigner::from_env("ZAPH_TEST_SURELY_UNSET_VAR").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_signer_protocol() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("signer.sh");
+        fs::write(
+            &program,
+            "#!/bin/sh\ncase \"$1\" in\n  address) echo 0x000000000000000000000000000000000000beef ;;\n  sign-transaction) grep -q '\"chainId\":\"0x7a69\"' && echo 0xf86b01 ;;\n  *) exit 1 ;;\nesac\n",
+        )
+        .unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let signer = CommandSigner::new(program.to_str().unwrap()).unwrap();
+        assert_eq!(signer.address(), Address::from_low_u64_be(0xbeef));
+
+        let raw = signer
+            .sign_transaction(&sample_tx(signer.address()))
+            .await
+            .unwrap();
+        assert_eq!(raw.0, vec![0xf8, 0x6b, 0x01]);
+        assert!(signer.sign_message(b"hi").await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_slow_command_signer_leaves_the_runtime_free() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("signer.sh");
+        fs::write(
+            &program,
+            "#!/bin/sh
+case \"$1\" in\n  address) echo 0x000000000000000000000000000000000000beef ;;\n  sign-message) sleep 1; echo 00 ;;\nesac\n",
+        )
+        .unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        let signer = CommandSigner::new(program.to_str().unwrap()).unwrap();
+
+        // On this single-threaded runtime, a blocking wait would hold the timer back too
+        let started = std::time::Instant::now();
+        tokio::select! {
+            _ = signer.sign_message(b"hi") => panic!("the signer should still be sleeping"),
+            _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {}
+        }
+        assert!(started.elapsed() < std::time::Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,83 @@
+use super::{signer::TransactionRequest, types::ContractSpecs};
+use web3::{
+    Web3,
+    contract::{Contract, Options, tokens::Tokenize},
+    transports::Http,
+    types::{BlockNumber, Bytes, CallRequest, H256, U256},
+};
+
+/// Builds a contract call transaction, has the specs' signer sign it and broadcasts it,
+/// returning the tx hash. Anything not set in `options` is filled in from the node.
+pub async fn send_transaction(
+    web3: &Web3<Http>,
+    contract: &Contract<Http>,
+    contract_specs: &mut ContractSpecs,
+    func: &str,
+    params: impl Tokenize,
+    options: Options,
+) -> Result<H256, anyhow::Error> {
+    let signer = contract_specs.signer()?;
+    let from = signer.address();
+    let data = contract
+        .abi()
+        .function(func)
+        .and_then(|function| function.encode_input(&params.into_tokens()))
+        .map_err(|e| anyhow::anyhow!("Failed to encode {func} call: {e}"))?;
+
+    let eth = web3.eth();
+    let chain_id = match contract_specs.ctx.chain_id {
+        Some(id) => U256::from(id),
+        None => eth.chain_id().await?,
+    };
+    let mut reservation = None;
+    let nonce = match (options.nonce, &contract_specs.nonces) {
+        (Some(nonce), _) => nonce,
+        (None, Some(nonces)) => {
+            let pending = async {
+                Ok(eth
+                    .transaction_count(from, Some(BlockNumber::Pending))
+                    .await?)
+            };
+            let reserved = nonces.reserve(chain_id, from, pending).await?;
+            let nonce = reserved.nonce;
+            reservation = Some(reserved);
+            nonce
+        }
+        (None, None) => {
+            eth.transaction_count(from, Some(BlockNumber::Pending))
+                .await?
+        }
+    };
+    let gas_price = match options.gas_price {
+        Some(price) => price,
+        None => eth.gas_price().await?,
+    };
+    let gas = match options.gas {
+        Some(gas) => gas,
+        None => {
+            let call = CallRequest {
+                from: Some(from),
+                to: Some(contract.address()),
+                data: Some(Bytes(data.clone())),
+                ..Default::default()
+            };
+            eth.estimate_gas(call, None).await?
+        }
+    };
+    let request = TransactionRequest {
+        from,
+        to: contract.address(),
+        gas,
+        gas_price,
+        value: options.value.unwrap_or_default(),
+        nonce,
+        data: Bytes(data),
+        chain_id,
+    };
+    let raw = signer.sign_transaction(&request).await?;
+    let tx_hash = eth.send_raw_transaction(raw).await?;
+    if let Some(reservation) = reservation {
+        reservation.commit();
+    }
+    Ok(tx_hash)
+}
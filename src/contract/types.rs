@@ -1,18 +1,56 @@
-use std::{fs, str::FromStr};
-use web3::signing::SecretKey;
+use super::{
+    agent::{AGENT_SOCK_ENV, AgentSigner},
+    external_signer::ClefSigner,
+    key_id,
+    nonce::NonceManager,
+    signer::{CommandSigner, LocalKeySigner, Signer},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, sync::Arc};
+use web3::types::{Address, H256};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NetworkContext {
     pub rpc_url: String,
     pub network: Option<String>,
+    pub explorer_url: Option<String>,
+    /// The chain ID the RPC endpoint must report before anything is signed, if known.
+    pub chain_id: Option<u64>,
+}
+
+impl NetworkContext {
+    /// Link to a transaction on the network's block explorer, if one is configured.
+    pub fn explorer_link(&self, tx_hash: &H256) -> Option<String> {
+        self.explorer_url
+            .as_ref()
+            .map(|template| template.replace("{tx}", &format!("{:#x}", tx_hash)))
+    }
+}
+
+/// Where the signing key for contract calls comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeySource {
+    /// A hex private key or encrypted v3 keystore file.
+    File(String),
+    /// An environment variable holding a hex private key.
+    Env(String),
+    /// A BIP-32 derivation path (e.g. `m/44'/60'/0'/0/3`) applied to the mnemonic.
+    HdPath(String),
+    /// An external signer (Clef-style JSON-RPC over HTTP or a Unix socket) holding `address`.
+    External { url: String, address: String },
+    /// A program run once per signing operation, see `signer::CommandSigner`.
+    Command(String),
 }
 
 #[derive(Debug)]
 pub struct ContractSpecs {
     pub ctx: NetworkContext,
     pub contract_addr: String,
-    pub priv_key_path: String,
-    pub priv_key: Option<SecretKey>, // Store the loaded private key here
+    pub key_source: KeySource,
+    pub signer: Option<Arc<dyn Signer>>, // Loaded from `key_source` on first use
+    /// Shared nonce bookkeeping when several transactions are in flight, else the node's
+    /// pending count is used.
+    pub nonces: Option<Arc<NonceManager>>,
 }
 
 #[derive(Debug)]
@@ -20,7 +58,13 @@ pub struct KeyData {
     pub id: String,
     pub owner: Option<String>,
     pub timeout: u64,
-    // Add other fields as needed for config management, e.g., ping_interval, custodians
+    /// Seconds between the daemon's pings, if not a share of `timeout`.
+    pub ping_interval: Option<u64>,
+    /// How the on-chain keyId is derived from `id`.
+    pub scheme: key_id::Scheme,
+    /// Our role when someone else owns the key, making the entry watch-only.
+    pub role: Option<Role>,
+    pub can_ping: bool,
 }
 
 #[derive(Debug)]
@@ -37,30 +81,62 @@ pub struct GasSpecs {
 }
 
 impl ContractSpecs {
-    pub fn load_private_key_if_missing(&mut self) -> Result<&SecretKey, anyhow::Error> {
-        if self.priv_key.is_none() {
-            let sk_bytes = fs::read_to_string(&self.priv_key_path)?
-                .trim()
-                .trim_start_matches("0x")
-                .to_string();
-
-            let sk = SecretKey::from_str(&sk_bytes).map_err(|_| {
-                anyhow::anyhow!(
-                    "Invalid private key format from path: {}",
-                    self.priv_key_path
-                )
-            })?;
-            self.priv_key = Some(sk);
+    /// The signer for contract calls, loaded from `key_source` on first use
+    /// (which may prompt for a keystore passphrase or the mnemonic).
+    pub fn signer(&mut self) -> Result<Arc<dyn Signer>, anyhow::Error> {
+        if let Some(signer) = &self.signer {
+            return Ok(signer.clone());
         }
-        Ok(self.priv_key.as_ref().unwrap())
+        let signer = self.key_source.load_signer()?;
+        self.signer = Some(signer.clone());
+        Ok(signer)
     }
 
-    pub fn get_private_key(&self) -> Option<&SecretKey> {
-        self.priv_key.as_ref()
+    /// Address of the account that signs transactions, loading the signer if needed.
+    pub fn signer_address(&mut self) -> Result<Address, anyhow::Error> {
+        Ok(self.signer()?.address())
+    }
+}
+
+impl KeySource {
+    /// Loads the signer, preferring the key agent when `ZAPH_AGENT_SOCK` is set and it holds
+    /// this key, so passphrase-protected keys don't need unlocking again. An agent that is gone
+    /// is skipped.
+    pub fn load_signer(&self) -> Result<Arc<dyn Signer>, anyhow::Error> {
+        if let Ok(socket) = std::env::var(AGENT_SOCK_ENV)
+            && let Some(signer) = AgentSigner::for_source(&socket, self)?
+        {
+            return Ok(Arc::new(signer));
+        }
+        self.load_local_signer()
+    }
+
+    /// Loads the signer in this process, prompting for passphrases or the mnemonic if needed.
+    pub fn load_local_signer(&self) -> Result<Arc<dyn Signer>, anyhow::Error> {
+        Ok(match self {
+            KeySource::File(path) => Arc::new(LocalKeySigner::from_file(path)?),
+            KeySource::Env(var) => Arc::new(LocalKeySigner::from_env(var)?),
+            KeySource::HdPath(path) => Arc::new(LocalKeySigner::from_mnemonic(path)?),
+            KeySource::External { url, address } => Arc::new(ClefSigner::new(url, address)?),
+            KeySource::Command(program) => Arc::new(CommandSigner::new(program)?),
+        })
+    }
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySource::File(path) => write!(f, "file {path}"),
+            KeySource::Env(var) => write!(f, "env ${var}"),
+            KeySource::HdPath(path) => write!(f, "mnemonic {path}"),
+            KeySource::External { url, address } => write!(f, "external {address} at {url}"),
+            KeySource::Command(program) => write!(f, "command {program}"),
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 #[repr(u8)] // This ensures it's represented as a u8, matching Solidity
 pub enum Role {
     Owner = 0,
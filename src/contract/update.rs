@@ -1,47 +1,43 @@
-use super::types::KeyData;
 use crate::contract::types::{ContractSpecs, GasSpecs};
+use crate::contract::{network, transaction};
 use dialoguer::Confirm;
 use std::str::FromStr;
 use web3::{
     contract::{Contract, Options},
-    signing::{Key, SecretKeyRef},
     transports::Http,
     types::{Address, Bytes, H256, U64, U256},
 };
 
+#[allow(clippy::too_many_arguments)] // mirrors the contract call plus gas controls
 pub async fn update_key_on_chain(
     contract_specs: &mut ContractSpecs,
     owner_address: &str,
-    key_data: KeyData,
+    key_hash: H256,
     new_data_hex: &str,
+    new_timeout: u64,
     yes: bool,
     gas_specs: GasSpecs,
     nonce: Option<u64>,
 ) -> Result<H256, anyhow::Error> {
     let http = Http::new(&contract_specs.ctx.rpc_url)?;
     let web3 = web3::Web3::new(http);
+    network::verify_chain_id(&web3, &contract_specs.ctx).await?;
 
     let contract_address = Address::from_str(&contract_specs.contract_addr)
         .map_err(|_| anyhow::anyhow!("Invalid contract address"))?;
 
-    let sk = if let Some(sec) = contract_specs.get_private_key() {
-        sec
-    } else {
-        contract_specs.load_private_key_if_missing()?
-    };
-    let wallet = SecretKeyRef::new(sk);
+    let from = contract_specs.signer_address()?;
 
     let abi_json = include_str!("../../abi/Zaphenath.json");
     let contract = Contract::from_json(web3.eth(), contract_address, abi_json.as_bytes())?;
 
-    let key_hash = H256::from_slice(web3::signing::keccak256(key_data.id.as_bytes()).as_slice());
     let owner = Address::from_str(owner_address)?;
     let new_data = Bytes::from(
         hex::decode(new_data_hex)
             .map_err(|_| anyhow::anyhow!("Invalid hex in --data: {}", new_data_hex))?,
     );
 
-    let call_params = (key_hash, owner, new_data, U256::from(key_data.timeout));
+    let call_params = (key_hash, owner, new_data, U256::from(new_timeout));
 
     let gas_to_use = if let Some(limit) = gas_specs.gas_limit {
         Some(U256::from(limit))
@@ -49,12 +45,7 @@ pub async fn update_key_on_chain(
         None
     } else {
         let est: U256 = contract
-            .estimate_gas(
-                "updateKey",
-                call_params.clone(),
-                wallet.address(),
-                Options::default(),
-            )
+            .estimate_gas("updateKey", call_params.clone(), from, Options::default())
             .await?;
 
         let gas_with_buffer = if let Some(buffer) = gas_specs.gas_buffer {
@@ -86,9 +77,15 @@ pub async fn update_key_on_chain(
         ..Options::default()
     };
 
-    let tx_hash = contract
-        .signed_call("updateKey", call_params, options, wallet)
-        .await?;
+    let tx_hash = transaction::send_transaction(
+        &web3,
+        &contract,
+        contract_specs,
+        "updateKey",
+        call_params,
+        options,
+    )
+    .await?;
 
     println!("⏳ Waiting for transaction {:?} to be mined...", tx_hash);
 
@@ -111,6 +108,9 @@ pub async fn update_key_on_chain(
     if let Some(status) = receipt.status {
         if status == U64::one() {
             println!("✏️ Key updated on-chain. Tx hash: {:?}", tx_hash);
+            if let Some(link) = contract_specs.ctx.explorer_link(&tx_hash) {
+                println!("🔗 {link}");
+            }
             Ok(tx_hash)
         } else {
             Err(anyhow::anyhow!(
@@ -17,12 +17,34 @@ struct Cli {
     #[arg(long)]
     config: Option<String>,
 
+    /// Use a named profile from the config for signer, network and contract defaults.
+    /// Falls back to the config's default profile when omitted.
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Read the passphrase for encrypted keystore files from this file instead of prompting.
+    /// The `ZAPHENATH_KEYSTORE_PASSWORD` environment variable works too.
+    #[arg(long)]
+    password_file: Option<String>,
+
+    /// Read the BIP-39 seed phrase used with `--hd-path` from this file (plain text,
+    /// or a v3 keystore holding the phrase) instead of prompting for it.
+    #[arg(long)]
+    mnemonic_file: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
 #[derive(Subcommand)]
 enum Commands {
+    /// Unlock signers once and serve signing requests over a Unix socket, like ssh-agent.
+    /// Commands and the daemon use it when `ZAPH_AGENT_SOCK` is set.
+    Agent {
+        #[command(flatten)]
+        args: cmd::agent::AgentArgs,
+    },
     /// Manage local configuration file.
     /// This includes viewing, initializing, or adding key entries to your local Zaphenath config.
     Config {
@@ -42,6 +64,27 @@ enum Commands {
         #[command(subcommand)]
         action: cmd::daemon::DaemonAction, // Use the DaemonAction from the new module
     },
+    /// Manage the network registry used to resolve `--network` names.
+    /// Lists the built-in networks and lets you add your own RPC endpoints and chain IDs.
+    Network {
+        #[command(subcommand)]
+        action: cmd::network::NetworkAction,
+    },
+    /// Manage named profiles bundling a signer, network and contract address.
+    Profile {
+        #[command(subcommand)]
+        action: cmd::profile::ProfileAction,
+    },
+    /// Rebuild secrets that were split into Shamir shares across custodians.
+    Recover {
+        #[command(subcommand)]
+        action: cmd::recover::RecoverAction,
+    },
+    /// Generate, import and inspect signing keys.
+    Wallet {
+        #[command(subcommand)]
+        action: cmd::wallet::WalletAction,
+    },
 }
 
 #[tokio::main]
@@ -60,7 +103,25 @@ async fn main() {
         unsafe { std::env::set_var("ZAPHENATH_CONFIG_PATH", custom_path) };
     }
 
+    if let Some(profile) = cli.profile {
+        // SAFETY: same as above, nothing else is running yet.
+        unsafe { std::env::set_var("ZAPHENATH_PROFILE", profile) };
+    }
+
+    if let Some(password_file) = cli.password_file {
+        // SAFETY: same as above, nothing else is running yet.
+        unsafe { std::env::set_var("ZAPHENATH_PASSWORD_FILE", password_file) };
+    }
+
+    if let Some(mnemonic_file) = cli.mnemonic_file {
+        // SAFETY: same as above, nothing else is running yet.
+        unsafe { std::env::set_var("ZAPHENATH_MNEMONIC_FILE", mnemonic_file) };
+    }
+
     match cli.command {
+        Commands::Agent { args } => {
+            cmd::agent::handle_agent_command(args).await;
+        }
         Commands::Config { action } => {
             cmd::config::handle_config_command(action).await;
         }
@@ -70,5 +131,17 @@ async fn main() {
         Commands::Daemon { action } => {
             cmd::daemon::handle_daemon_command(&action).await;
         }
+        Commands::Network { action } => {
+            cmd::network::handle_network_command(action).await;
+        }
+        Commands::Profile { action } => {
+            cmd::profile::handle_profile_command(action).await;
+        }
+        Commands::Recover { action } => {
+            cmd::recover::handle_recover_command(action).await;
+        }
+        Commands::Wallet { action } => {
+            cmd::wallet::handle_wallet_command(action).await;
+        }
     }
 }
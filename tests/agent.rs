@@ -0,0 +1,109 @@
+use serde_json::Value;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::Duration;
+use tempfile::tempdir;
+
+// Anvil's first default account
+const OWNER_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const OWNER: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+fn config_add(config_path: &str, key_id: &str) -> Command {
+    let mut cmd = Command::new("cargo");
+    cmd.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path,
+        "config",
+        "add",
+        "--key-id",
+        key_id,
+        "--contract-address",
+        "0x0000000000000000000000000000000000000001",
+        "--private-key-env",
+        "ZAPH_TEST_AGENT_KEY",
+        "--network",
+        "anvil",
+        "--timeout",
+        "60",
+    ]);
+    cmd
+}
+
+#[test]
+fn test_agent_signs_for_keys_the_client_cannot_load() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    // A socket path we choose lives in our directory, which the agent must leave alone
+    let socket_dir = dir.path().join("sockets");
+    fs::create_dir(&socket_dir).unwrap();
+    fs::set_permissions(&socket_dir, fs::Permissions::from_mode(0o755)).unwrap();
+    let socket = socket_dir.join("agent.sock");
+
+    let status = config_add(config_path, "agent_key")
+        .env("ZAPH_TEST_AGENT_KEY", OWNER_KEY)
+        .status()
+        .expect("Failed to run config add");
+    assert!(status.success(), "config add failed");
+
+    let mut agent = Command::new(env!("CARGO_BIN_EXE_zaph"))
+        .args(["--config", config_path, "agent", "--idle-timeout", "30"])
+        .arg("--socket")
+        .arg(&socket)
+        .env("ZAPH_TEST_AGENT_KEY", OWNER_KEY)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("Failed to start agent");
+
+    for _ in 0..100 {
+        if socket.exists() {
+            break;
+        }
+        sleep(Duration::from_millis(100));
+    }
+    assert!(socket.exists(), "agent socket was not created");
+    let mode = fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600, "agent socket is not owner-only");
+    let mode = fs::metadata(&socket_dir).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755, "agent changed the socket's directory");
+
+    // The key's environment variable is not set here, only the agent holds it.
+    let status = config_add(config_path, "second_key")
+        .env_remove("ZAPH_TEST_AGENT_KEY")
+        .env("ZAPH_AGENT_SOCK", &socket)
+        .status()
+        .expect("Failed to run config add");
+
+    agent.kill().ok();
+    agent.wait().ok();
+    assert!(status.success(), "config add through the agent failed");
+
+    let contents = fs::read_to_string(config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    assert_eq!(config_json["keys"][1]["key_id"], "second_key");
+    assert_eq!(config_json["keys"][1]["owner"], OWNER);
+}
+
+#[test]
+fn test_stale_agent_socket_falls_back_to_the_local_key() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    // As left behind by an agent that exited after its idle timeout
+    let output = config_add(config_path.to_str().unwrap(), "local_key")
+        .env("ZAPH_TEST_AGENT_KEY", OWNER_KEY)
+        .env("ZAPH_AGENT_SOCK", dir.path().join("gone.sock"))
+        .output()
+        .expect("Failed to run config add");
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unreachable"));
+
+    let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    assert_eq!(config_json["keys"][0]["owner"], OWNER);
+}
@@ -62,3 +62,64 @@ fn test_cli_config_add_and_view_with_network_and_rpc_url() {
     assert!(out_str.contains("sepolia"));
     assert!(out_str.contains("localhost:8545"));
 }
+
+#[test]
+fn test_watch_only_entry_limits_commands() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let zaph = |args: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    // No signer: we only watch this key, as a reader that may ping
+    let output = zaph(&[
+        "config",
+        "add",
+        "--key-id",
+        "theirs",
+        "--contract-address",
+        "0xdeadbeef",
+        "--network",
+        "sepolia",
+        "--timeout",
+        "600",
+        "--owner",
+        "0x123abc",
+        "--role",
+        "reader",
+        "--can-ping",
+    ]);
+    assert!(output.status.success(), "{output:?}");
+
+    let contents = fs::read_to_string(&config_path).unwrap();
+    assert!(contents.contains("\"role\": \"reader\""));
+    assert!(contents.contains("\"can_ping\": true"));
+    assert!(!contents.contains("private_key_path"));
+
+    let output = zaph(&["contract", "delete-key", "--key-id", "theirs", "--yes"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("watch-only"));
+
+    // Pinging on the owner's behalf needs a signer of ours, given for this run
+    let output = zaph(&[
+        "contract",
+        "ping-key",
+        "--key-id",
+        "theirs",
+        "--private-key-path",
+        "/dev/null",
+        "--mock",
+    ]);
+    assert!(output.status.success(), "{output:?}");
+}
@@ -0,0 +1,92 @@
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_config_add_derives_owner_from_hd_path() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let mnemonic_path = dir.path().join("seed.txt");
+    fs::write(
+        &mnemonic_path,
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about\n",
+    )
+    .unwrap();
+
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "--mnemonic-file",
+            mnemonic_path.to_str().unwrap(),
+            "config",
+            "add",
+            "--key-id",
+            "hd_key",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--hd-path",
+            "m/44'/60'/0'/0/0",
+            "--network",
+            "anvil",
+            "--timeout",
+            "60",
+        ])
+        .status()
+        .expect("Failed to run config add");
+    assert!(status.success(), "config add failed");
+
+    let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    let entry = &config_json["keys"][0];
+
+    assert_eq!(entry["hd_path"], "m/44'/60'/0'/0/0");
+    assert_eq!(entry["owner"], "0x9858effd232b4033e47d90003d41ec34ecaeda94");
+    assert!(entry.get("private_key_path").is_none());
+}
+
+#[test]
+fn test_config_add_reads_key_from_env_var() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "config",
+            "add",
+            "--key-id",
+            "env_key",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--private-key-env",
+            "ZAPH_TEST_OWNER_KEY",
+            "--network",
+            "anvil",
+            "--timeout",
+            "60",
+        ])
+        // Anvil's first default account
+        .env(
+            "ZAPH_TEST_OWNER_KEY",
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .status()
+        .expect("Failed to run config add");
+    assert!(status.success(), "config add failed");
+
+    let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    let entry = &config_json["keys"][0];
+
+    assert_eq!(entry["private_key_env"], "ZAPH_TEST_OWNER_KEY");
+    assert_eq!(entry["owner"], "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+}
@@ -53,4 +53,95 @@ fn test_contract_create_key_adds_to_config() {
     let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
     assert!(contents.contains(key_id));
     assert!(contents.contains(&contract_address));
+
+    // Creating it again is refused before anything is sent
+    let output = cmd.output().expect("Failed to run CLI");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+}
+
+#[test]
+fn test_contract_create_key_salted_id_is_stable_and_hidden() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+    let Ok(private_key_path) = env::var("ZAPHENATH_TEST_PRIVKEY") else {
+        eprintln!("ZAPHENATH_TEST_PRIVKEY not set, skipping");
+        return;
+    };
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let create = |key_id: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+                "contract",
+                "create-key",
+                "--key-id",
+                key_id,
+                "--salted-id",
+                "--data",
+                "00ffcc",
+                "--timeout",
+                "120",
+                "--rpc-url",
+                "http://localhost:8545",
+                "--contract-address",
+                "0x0000000000000000000000000000000000000001",
+                "--private-key-path",
+                &private_key_path,
+                "--yes",
+                "--mock",
+            ])
+            .status()
+            .expect("Failed to run CLI")
+    };
+    assert!(create("salted").success());
+
+    let config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    let key_hash = config["keys"][0]["key_hash"]
+        .as_str()
+        .expect("salted key has no key_hash")
+        .to_string();
+    let plain = format!(
+        "0x{}",
+        hex::encode(web3::signing::keccak256("salted".as_bytes()))
+    );
+    assert_ne!(key_hash, plain);
+
+    // The same owner derives the same ID when the key is added back to a fresh config.
+    fs::remove_file(&config_path).unwrap();
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "config",
+            "add",
+            "--key-id",
+            "salted",
+            "--salted-id",
+            "--timeout",
+            "120",
+            "--network",
+            "mainnet",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--private-key-path",
+            &private_key_path,
+        ])
+        .status()
+        .expect("Failed to run CLI");
+    assert!(status.success());
+
+    let config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["keys"][0]["key_hash"], key_hash.as_str());
 }
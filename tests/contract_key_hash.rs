@@ -0,0 +1,128 @@
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn zaph(config_path: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+        ])
+        .args(args)
+        .output()
+        .expect("Failed to run CLI")
+}
+
+#[test]
+fn test_key_hash_computes_offline() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+
+    let output = zaph(
+        &config_path,
+        &[
+            "contract",
+            "key-hash",
+            "--key-id",
+            "my-key",
+            "--owner",
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = hex::encode(web3::signing::keccak256(b"my-key"));
+    assert!(stdout.contains(&expected), "{stdout}");
+    assert!(stdout.contains("Full key"), "{stdout}");
+}
+
+#[test]
+fn test_key_added_by_hash_can_be_pinged_by_hash() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    let key_hash = format!("0x{}", "5a".repeat(32));
+
+    let output = zaph(
+        &config_path,
+        &[
+            "config",
+            "add",
+            "--key-hash",
+            &key_hash,
+            "--owner",
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "--private-key-path",
+            "/dev/null",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--network",
+            "mainnet",
+            "--timeout",
+            "60",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let output = zaph(
+        &config_path,
+        &["contract", "ping-key", "--key-hash", &key_hash, "--mock"],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    let key = &config["keys"][0];
+    assert_eq!(key["key_id"], key_hash.as_str());
+    assert_eq!(key["key_hash"], key_hash.as_str());
+    assert!(key["last_ping_timestamp"].is_i64());
+
+    // A malformed hash is rejected before anything runs
+    let output = zaph(
+        &config_path,
+        &["contract", "ping-key", "--key-hash", "0x1234", "--mock"],
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_salted_key_hash_needs_the_owners_signer() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    let key_path = dir.path().join("key.hex");
+    fs::write(
+        &key_path,
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    )
+    .unwrap();
+
+    let key_hash = |owner: &str| {
+        zaph(
+            &config_path,
+            &[
+                "contract",
+                "key-hash",
+                "--key-id",
+                "my-key",
+                "--salted-id",
+                "--owner",
+                owner,
+                "--private-key-path",
+                key_path.to_str().unwrap(),
+            ],
+        )
+    };
+
+    // The signer's own address
+    let output = key_hash("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+    assert!(output.status.success(), "{output:?}");
+
+    // Someone else's would give a salt that isn't theirs
+    let output = key_hash("0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("owner's signer"), "{stderr}");
+}
@@ -108,3 +108,55 @@ fn test_ping_nonexistent_key_should_fail() {
         "Expected error about key not found, got: {stderr}"
     );
 }
+
+#[test]
+fn test_ping_ad_hoc_key_without_config_entry() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let ping = |extra: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+                "contract",
+                "ping-key",
+                "--key-id",
+                "someone_elses_key",
+                "--owner",
+                "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+                "--contract-address",
+                "0x0000000000000000000000000000000000000001",
+                "--rpc-url",
+                "http://localhost:8545",
+                "--private-key-path",
+                "/dev/null",
+                "--mock",
+            ])
+            .args(extra)
+            .status()
+            .expect("Failed to run CLI")
+    };
+
+    // Without --save the config is never written
+    assert!(ping(&[]).success(), "ad-hoc ping failed");
+    assert!(!config_path.exists());
+
+    // With --save the key is recorded, along with the ping
+    assert!(
+        ping(&["--save"]).success(),
+        "ad-hoc ping with --save failed"
+    );
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let key = &config["keys"][0];
+    assert_eq!(key["key_id"], "someone_elses_key");
+    assert_eq!(key["owner"], "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+    assert!(key["last_ping_timestamp"].is_i64());
+
+    // Saving it twice is refused before anything is sent
+    assert!(!ping(&["--save"]).success());
+}
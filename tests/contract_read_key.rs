@@ -79,6 +79,169 @@ fn test_create_and_read_key_succeeds() {
     );
 }
 
+#[test]
+fn test_create_encrypted_and_read_decrypts() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let plaintext_path = dir.path().join("secret.txt");
+    std::fs::write(&plaintext_path, "Hello, custodians!").unwrap();
+    let key_id = "encrypted_key_to_read";
+
+    let mock = env::var("ZAPHENATH_TEST_MOCK").unwrap_or_else(|_| "true".into()) == "true";
+    let contract_address = env::var("ZAPHENATH_TEST_CONTRACT")
+        .unwrap_or_else(|_| "0x0000000000000000000000000000000000000001".into());
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let rpc_url = env::var("ZAPHENATH_TEST_RPC").unwrap_or("http://localhost:8545".into());
+
+    let mut create = Command::new("cargo");
+    create.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path.to_str().unwrap(),
+        "contract",
+        "create-key",
+        "--key-id",
+        key_id,
+        "--plaintext-file",
+        plaintext_path.to_str().unwrap(),
+        // Public key of Anvil's first default account, the test signer
+        "--encrypt-for",
+        "0x038318535b54105d4a7aae60c08fc45f9687181b4fdfc625bd1a753fa7397fed75",
+        "--timeout",
+        "0",
+        "--rpc-url",
+        &rpc_url,
+        "--contract-address",
+        &contract_address,
+        "--private-key-path",
+        &private_key_path,
+        "--yes",
+        "--gas-buffer",
+        "1.1",
+    ]);
+    if mock {
+        create.arg("--mock");
+    } else {
+        thread::sleep(Duration::from_secs(1)); // Wait for some blocks to be mined
+    }
+    assert!(create.status().unwrap().success(), "key creation failed");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "contract",
+            "read-key",
+            "--key-id",
+            key_id,
+            "--decrypt",
+            "--private-key-path",
+            &private_key_path,
+            "--decode",
+        ])
+        .output()
+        .expect("Failed to run read-key");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        output.status.success(),
+        "read-key --decrypt should succeed. stderr: {stderr}"
+    );
+    assert!(
+        stdout.contains("Hello, custodians!"),
+        "Decrypted output should contain the plaintext. Output: {stdout}"
+    );
+}
+
+#[test]
+fn test_create_with_passphrase_and_read_decrypts() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let plaintext_path = dir.path().join("letter.txt");
+    std::fs::write(&plaintext_path, "Hello, lawyer!").unwrap();
+    let passphrase_path = dir.path().join("passphrase.txt");
+    std::fs::write(&passphrase_path, "correct horse battery staple").unwrap();
+    let key_id = "passphrase_key_to_read";
+
+    let mock = env::var("ZAPHENATH_TEST_MOCK").unwrap_or_else(|_| "true".into()) == "true";
+    let contract_address = env::var("ZAPHENATH_TEST_CONTRACT")
+        .unwrap_or_else(|_| "0x0000000000000000000000000000000000000001".into());
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let rpc_url = env::var("ZAPHENATH_TEST_RPC").unwrap_or("http://localhost:8545".into());
+
+    let mut create = Command::new("cargo");
+    create.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path.to_str().unwrap(),
+        "contract",
+        "create-key",
+        "--key-id",
+        key_id,
+        "--plaintext-file",
+        plaintext_path.to_str().unwrap(),
+        "--encrypt-with-passphrase",
+        "--passphrase-file",
+        passphrase_path.to_str().unwrap(),
+        "--timeout",
+        "0",
+        "--rpc-url",
+        &rpc_url,
+        "--contract-address",
+        &contract_address,
+        "--private-key-path",
+        &private_key_path,
+        "--yes",
+    ]);
+    if mock {
+        create.arg("--mock");
+    } else {
+        thread::sleep(Duration::from_secs(1)); // Wait for some blocks to be mined
+    }
+    assert!(create.status().unwrap().success(), "key creation failed");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "contract",
+            "read-key",
+            "--key-id",
+            key_id,
+            "--decrypt",
+            "--passphrase-file",
+            passphrase_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run read-key");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        output.status.success(),
+        "read-key --decrypt should succeed. stderr: {stderr}"
+    );
+    assert!(
+        stdout.contains("Hello, lawyer!"),
+        "Decrypted output should contain the plaintext. Output: {stdout}"
+    );
+}
+
 #[test]
 fn test_read_nonexistent_key_fails() {
     let _ = dotenvy::from_filename(".env.test").ok();
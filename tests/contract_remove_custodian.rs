@@ -93,7 +93,7 @@ fn test_create_key_set_and_remove_custodian_succeeds() {
     let contents_after_set = fs::read_to_string(&config_path).expect("Failed to read config file");
     let config_json_after_set: Value =
         serde_json::from_str(&contents_after_set).expect("Failed to parse config JSON");
-    let key_entry_after_set = config_json_after_set
+    let key_entry_after_set = config_json_after_set["keys"]
         .as_array()
         .unwrap()
         .iter()
@@ -151,9 +151,9 @@ fn test_create_key_set_and_remove_custodian_succeeds() {
     let config_json: Value =
         serde_json::from_str(&final_contents).expect("Failed to parse config JSON");
 
-    let key_entry = config_json
+    let key_entry = config_json["keys"]
         .as_array()
-        .expect("Config should be an array")
+        .expect("Config keys should be an array")
         .iter()
         .find(|entry| entry.get("key_id").and_then(Value::as_str) == Some(key_id))
         .expect("Key entry not found after removal");
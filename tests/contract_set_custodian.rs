@@ -99,9 +99,9 @@ fn test_create_key_and_set_custodian_succeeds() {
     let config_json: Value =
         serde_json::from_str(&final_contents).expect("Failed to parse config JSON");
 
-    let key_entry = config_json
+    let key_entry = config_json["keys"]
         .as_array()
-        .expect("Config should be an array")
+        .expect("Config keys should be an array")
         .iter()
         .find(|entry| entry.get("key_id").and_then(Value::as_str) == Some(key_id))
         .expect("Key entry not found after setting custodian");
@@ -127,3 +127,88 @@ fn test_update_nonexistent_key_fails() {
         "Expected error about key not found, got: {stderr}"
     );
 }
+
+#[test]
+fn test_update_key_encrypts_plaintext_file() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let plaintext_path = dir.path().join("secret.txt");
+    fs::write(&plaintext_path, "Hello, custodians!").unwrap();
+    let key_id = "key_to_encrypt";
+
+    let mock = env::var("ZAPHENATH_TEST_MOCK").unwrap_or_else(|_| "true".into()) == "true";
+    let contract_address = env::var("ZAPHENATH_TEST_CONTRACT")
+        .unwrap_or_else(|_| "0x0000000000000000000000000000000000000001".into());
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let rpc_url = env::var("ZAPHENATH_TEST_RPC").unwrap_or("http://localhost:8545".into());
+
+    let mut create = Command::new("cargo");
+    create.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path.to_str().unwrap(),
+        "contract",
+        "create-key",
+        "--key-id",
+        key_id,
+        "--data",
+        "abc123",
+        "--timeout",
+        "300",
+        "--rpc-url",
+        &rpc_url,
+        "--contract-address",
+        &contract_address,
+        "--private-key-path",
+        &private_key_path,
+        "--yes",
+    ]);
+    if mock {
+        create.arg("--mock");
+    }
+    assert!(create.status().unwrap().success(), "key creation failed");
+
+    let mut update = Command::new("cargo");
+    update.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path.to_str().unwrap(),
+        "contract",
+        "update-key",
+        "--key-id",
+        key_id,
+        "--plaintext-file",
+        plaintext_path.to_str().unwrap(),
+        // Public key of Anvil's first default account
+        "--encrypt-for",
+        "0x038318535b54105d4a7aae60c08fc45f9687181b4fdfc625bd1a753fa7397fed75",
+        "--timeout",
+        "600",
+        "--yes",
+    ]);
+    if mock {
+        update.arg("--mock");
+    }
+    assert!(
+        update.status().unwrap().success(),
+        "update-key should succeed"
+    );
+
+    let config_contents = fs::read_to_string(&config_path).expect("Failed to read config");
+    let config_json: serde_json::Value = serde_json::from_str(&config_contents).unwrap();
+    let data = config_json["keys"][0]["data"].as_str().unwrap();
+    assert!(
+        data.starts_with("5a454301"),
+        "Data should be an encrypted envelope, got: {data}"
+    );
+    assert!(
+        !data.contains(&hex::encode("Hello")),
+        "Plaintext leaked into the stored data"
+    );
+}
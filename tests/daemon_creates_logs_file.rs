@@ -67,10 +67,16 @@ fn test_daemon_logs_file_is_created() {
         daemon.arg("--mock");
     }
 
+    daemon.env("ZAPHENATH_STATE_DIR", dir.path().join("state"));
     let _ = daemon.status().expect("Failed to run daemon CLI");
 
-    // Check that log file was created and is not empty
-    let log_contents = fs::read_to_string(".zaphenathd.log").expect("Missing log file");
+    // Check that log file was created in the state directory and is not empty
+    let log_path = fs::read_dir(dir.path().join("state"))
+        .expect("Missing state directory")
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .expect("Missing log file");
+    let log_contents = fs::read_to_string(log_path).expect("Missing log file");
     assert!(
         log_contents.contains("Pinged key") || log_contents.contains("🔁 Starting ping cycle"),
         "Expected log output not found"
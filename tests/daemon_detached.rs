@@ -1,29 +1,46 @@
-use std::{fs, path::Path, process::Command, thread, time::Duration};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::Duration,
+};
+use tempfile::tempdir;
+
+/// The daemon's file with `extension` in the state directory, if there is one.
+fn state_file(state_dir: &Path, extension: &str) -> Option<PathBuf> {
+    fs::read_dir(state_dir)
+        .ok()?
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == extension))
+}
 
 #[test]
 fn test_daemon_detached_and_stop() {
     let _ = dotenvy::from_filename(".env.test");
 
-    // Clean up any old state
-    let _ = fs::remove_file(".zaphenathd.pid");
-    let _ = fs::remove_file(".zaphenathd.log");
+    // A state directory of our own, so no old state gets in the way
+    let dir = tempdir().unwrap();
+    let state_dir = dir.path().join("state");
 
     // Start the daemon in detached mode
     let mut daemon = Command::new("cargo");
-    daemon.args([
-        "run",
-        "--quiet",
-        "--",
-        "daemon",
-        "run",
-        "--interval",
-        "1",
-        "--detached",
-        "--nonce",
-        "700",
-        "--gas-buffer",
-        "1.2",
-    ]);
+    daemon
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "daemon",
+            "run",
+            "--interval",
+            "1",
+            "--detached",
+            "--nonce",
+            "700",
+            "--gas-buffer",
+            "1.2",
+        ])
+        .env("ZAPHENATH_STATE_DIR", &state_dir);
     let child = daemon.spawn().expect("Failed to start detached daemon");
     drop(child); // ✅ Explicitly drop the child to silence clippy warning
 
@@ -31,21 +48,21 @@ fn test_daemon_detached_and_stop() {
     thread::sleep(Duration::from_secs(3));
 
     // ✅ Check if PID file exists
-    assert!(
-        Path::new(".zaphenathd.pid").exists(),
-        "❌ PID file not created"
-    );
+    let pid_path = state_file(&state_dir, "pid").expect("❌ PID file not created");
 
     // ✅ Check if log file exists and has expected content
-    let log_contents = fs::read_to_string(".zaphenathd.log").expect("❌ Log file not found");
+    let log_path = state_file(&state_dir, "log").expect("❌ Log file not found");
+    let log_contents = fs::read_to_string(log_path).expect("❌ Log file not found");
     assert!(
         log_contents.contains("Daemon started"),
         "❌ Daemon startup log not found"
     );
 
-    // 🛑 Send stop signal via CLI
-    let stop_status = Command::new("cargo")
-        .args(["run", "--quiet", "--", "daemon", "stop"])
+    // 🛑 Send stop signal via CLI, from another directory: it finds the daemon all the same
+    let stop_status = Command::new(env!("CARGO_BIN_EXE_zaph"))
+        .args(["daemon", "stop"])
+        .env("ZAPHENATH_STATE_DIR", &state_dir)
+        .current_dir(dir.path())
         .status()
         .expect("Failed to run daemon stop");
 
@@ -55,8 +72,5 @@ fn test_daemon_detached_and_stop() {
     thread::sleep(Duration::from_secs(1));
 
     // ✅ PID file should be gone
-    assert!(
-        !Path::new(".zaphenathd.pid").exists(),
-        "❌ PID file still exists after stop"
-    );
+    assert!(!pid_path.exists(), "❌ PID file still exists after stop");
 }
@@ -0,0 +1,63 @@
+use serde_json::Value;
+use std::{fs, process::Command};
+use tempfile::tempdir;
+
+#[test]
+fn test_daemon_failed_ping_keeps_timestamp_and_records_health() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("daemon_retry_config.json");
+    let key_path = dir.path().join("key.hex");
+    fs::write(
+        &key_path,
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    )
+    .unwrap();
+
+    let zaph = |args: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .args(args)
+            .env("ZAPHENATH_STATE_DIR", dir.path().join("state"))
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    // Nothing listens on this RPC, so every ping fails
+    let output = zaph(&[
+        "config",
+        "add",
+        "--key-id",
+        "unreachable",
+        "--contract-address",
+        "0x0000000000000000000000000000000000000001",
+        "--rpc-url",
+        "http://127.0.0.1:9",
+        "--private-key-path",
+        key_path.to_str().unwrap(),
+        "--timeout",
+        "86400",
+    ]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = zaph(&["daemon", "run", "--shots", "1"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let config: Value = serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    let key = &config["keys"][0];
+    assert!(key.get("last_ping_timestamp").is_none());
+    assert_eq!(key["health"]["consecutive_failures"], 1);
+    assert!(key["health"]["last_error"].is_string());
+    assert!(key["health"]["retry_at"].is_i64());
+
+    let output = zaph(&["daemon", "status"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Last ping: never"), "{stdout}");
+    assert!(stdout.contains("Failures:  1 in a row"), "{stdout}");
+}
@@ -0,0 +1,118 @@
+use nix::{
+    sys::signal::{Signal, kill},
+    unistd::Pid,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+    time::Duration,
+};
+use tempfile::tempdir;
+
+fn wait_for(what: &str, condition: impl Fn() -> bool) {
+    for _ in 0..50 {
+        if condition() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    panic!("❌ Timed out waiting for {what}");
+}
+
+/// The daemon's file with `extension` in the state directory, if there is one.
+fn state_file(state_dir: &Path, extension: &str) -> Option<PathBuf> {
+    fs::read_dir(state_dir)
+        .ok()?
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == extension))
+}
+
+#[test]
+fn test_daemon_is_single_instance_reloads_and_stops_cleanly() {
+    let dir = tempdir().unwrap();
+    let state_dir = dir.path().join("state");
+    let config_path = dir.path().join("daemon_signals_config.json");
+    let other_config_path = dir.path().join("other_config.json");
+
+    let zaph = |config_path: &Path| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_zaph"));
+        command
+            .args(["--config", config_path.to_str().unwrap()])
+            .env("ZAPHENATH_STATE_DIR", &state_dir);
+        command
+    };
+    let run = |config_path: &Path, args: &[&str]| -> Output {
+        zaph(config_path)
+            .args(args)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    for config_path in [&config_path, &other_config_path] {
+        assert!(run(config_path, &["config", "init"]).status.success());
+    }
+
+    let mut daemon = zaph(&config_path)
+        .args(["daemon", "run", "--interval", "3600"])
+        .spawn()
+        .expect("Failed to start daemon");
+    wait_for("the PID file", || state_file(&state_dir, "pid").is_some());
+    let pid_path = state_file(&state_dir, "pid").unwrap();
+    let log_path = state_file(&state_dir, "log").unwrap();
+    let pid: i32 = fs::read_to_string(&pid_path)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+
+    // A second daemon for the same config refuses to start while the first is alive...
+    let second = run(&config_path, &["daemon", "run", "--shots", "1"]);
+    assert!(!second.status.success());
+    assert!(String::from_utf8_lossy(&second.stderr).contains("already running"));
+    // ...but one for another config runs alongside it
+    let other = run(&other_config_path, &["daemon", "run", "--shots", "1"]);
+    assert!(other.status.success(), "{other:?}");
+
+    // SIGHUP wakes it up for a fresh cycle
+    kill(Pid::from_raw(pid), Signal::SIGHUP).unwrap();
+    wait_for("the reload", || {
+        fs::read_to_string(&log_path).is_ok_and(|log| log.contains("Reloading config"))
+    });
+
+    // The subcommands' own --config finds it too, whatever the default config is
+    let daemon_command = |subcommand: &str| -> Output {
+        Command::new(env!("CARGO_BIN_EXE_zaph"))
+            .args([
+                "daemon",
+                subcommand,
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .env("ZAPHENATH_STATE_DIR", &state_dir)
+            .env("ZAPHENATH_CONFIG_PATH", dir.path().join("unused.json"))
+            .output()
+            .expect("Failed to run CLI")
+    };
+    let status = daemon_command("status");
+    assert!(status.status.success(), "{status:?}");
+    assert!(String::from_utf8_lossy(&status.stdout).contains(&format!("PID {pid}")));
+
+    let stop = daemon_command("stop");
+    assert!(stop.status.success(), "{stop:?}");
+    assert!(daemon.wait().unwrap().success());
+    assert!(!pid_path.exists());
+    assert!(
+        fs::read_to_string(&log_path)
+            .unwrap()
+            .contains("Daemon stopped")
+    );
+
+    // A daemon that died without cleaning up leaves a stale PID file, which stop removes
+    fs::write(&pid_path, "2147483647\n").unwrap();
+    let stop = run(&config_path, &["daemon", "stop"]);
+    assert!(stop.status.success());
+    assert!(String::from_utf8_lossy(&stop.stdout).contains("stale"));
+    assert!(!pid_path.exists());
+}
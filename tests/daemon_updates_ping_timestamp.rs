@@ -71,6 +71,7 @@ fn test_daemon_run_updates_ping_timestamp() {
     }
 
     // Use a thread to kill the daemon early if it were real — but we're mocking so we just run it once
+    daemon.env("ZAPHENATH_STATE_DIR", dir.path().join("state"));
     let status = daemon.status().expect("Failed to run daemon CLI");
     assert!(status.success(), "Daemon run failed");
 
@@ -78,9 +79,9 @@ fn test_daemon_run_updates_ping_timestamp() {
     let config_str = fs::read_to_string(&config_path).expect("Failed to read config file");
     let config_json: Value = serde_json::from_str(&config_str).expect("Invalid JSON in config");
 
-    let entry = config_json
+    let entry = config_json["keys"]
         .as_array()
-        .expect("Config should be array")
+        .expect("Config keys should be array")
         .iter()
         .find(|e| e.get("key_id").and_then(Value::as_str) == Some(key_id))
         .expect("Key entry not found");
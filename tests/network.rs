@@ -0,0 +1,106 @@
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn zaph(config_path: &str, args: &[&str]) -> std::process::Output {
+    Command::new("cargo")
+        .args(["run", "--quiet", "--", "--config", config_path])
+        .args(args)
+        .output()
+        .expect("Failed to run CLI")
+}
+
+#[test]
+fn test_network_add_list_remove() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config = config_path.to_str().unwrap();
+
+    // 1. Add a user-defined network
+    let output = zaph(
+        config,
+        &[
+            "network",
+            "add",
+            "--name",
+            "devnet",
+            "--rpc-url",
+            "http://localhost:9545",
+            "--chain-id",
+            "1337",
+        ],
+    );
+    assert!(output.status.success(), "network add failed");
+
+    let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    assert_eq!(config_json["networks"][0]["name"], "devnet");
+    assert_eq!(config_json["networks"][0]["chain_id"], 1337);
+
+    // 2. It is listed next to the built-ins
+    let output = zaph(config, &["network", "list"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "network list failed");
+    assert!(
+        stdout.contains("devnet (chain 1337, user)"),
+        "got: {stdout}"
+    );
+    assert!(
+        stdout.contains("anvil (chain 31337, built-in)"),
+        "got: {stdout}"
+    );
+
+    // 3. Built-ins can't be removed, user networks can
+    let output = zaph(config, &["network", "remove", "--name", "anvil"]);
+    assert!(!output.status.success(), "removing a built-in should fail");
+
+    let output = zaph(config, &["network", "remove", "--name", "devnet"]);
+    assert!(output.status.success(), "network remove failed");
+}
+
+#[test]
+fn test_network_errors_name_the_cause() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config = config_path.to_str().unwrap();
+
+    let output = zaph(
+        config,
+        &[
+            "network",
+            "add",
+            "--name",
+            "infura",
+            "--rpc-url",
+            "https://mainnet.infura.io/v3/${ZAPH_TEST_UNSET_API_KEY}",
+            "--chain-id",
+            "1",
+        ],
+    );
+    assert!(output.status.success(), "network add failed");
+
+    for (network, cause) in [
+        ("nope", "Unknown network name"),
+        ("infura", "ZAPH_TEST_UNSET_API_KEY"),
+    ] {
+        let output = zaph(
+            config,
+            &[
+                "contract",
+                "key-hash",
+                "--key-id",
+                "k",
+                "--owner",
+                "0x0000000000000000000000000000000000000001",
+                "--contract-address",
+                "0x0000000000000000000000000000000000000001",
+                "--network",
+                network,
+            ],
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success());
+        assert!(stderr.contains(cause), "{stderr}");
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::{env, fs};
+use tempfile::tempdir;
+
+fn create_mock_key(config_path: &str, key_id: &str) {
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "contract",
+            "create-key",
+            "--key-id",
+            key_id,
+            "--data",
+            "abc123",
+            "--timeout",
+            "300",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--private-key-path",
+            &private_key_path,
+            "--network",
+            "anvil",
+            "--mock",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success(), "key creation failed");
+}
+
+fn stored_data(config_path: &str) -> Vec<u8> {
+    let contents = fs::read_to_string(config_path).unwrap();
+    let config_json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    hex::decode(config_json["keys"][0]["data"].as_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_update_key_packs_file_with_metadata() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let file_path = dir.path().join("notes.txt");
+    fs::write(&file_path, "packed ".repeat(50)).unwrap();
+    create_mock_key(config_path, "packed_key");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "contract",
+            "update-key",
+            "--key-id",
+            "packed_key",
+            "--plaintext-file",
+            file_path.to_str().unwrap(),
+            "--compress",
+            "--timeout",
+            "300",
+            "--mock",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "update-key failed");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("unencrypted"),
+        "Expected a warning about storing plaintext"
+    );
+
+    let data = stored_data(config_path);
+    assert!(data.starts_with(b"ZPL\x01\x01"), "Expected a gzip payload");
+    let text = String::from_utf8_lossy(&data);
+    assert!(text.contains("text/plain"));
+    assert!(text.contains("notes.txt"));
+    assert!(data.len() < 350, "Payload was not compressed");
+}
+
+#[test]
+fn test_update_key_reads_payload_from_stdin() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    create_mock_key(config_path, "stdin_key");
+
+    let mut child = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "contract",
+            "update-key",
+            "--key-id",
+            "stdin_key",
+            "--plaintext-file",
+            "-",
+            "--content-type",
+            "application/json",
+            "--timeout",
+            "300",
+            "--mock",
+        ])
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(br#"{"seed": "words"}"#)
+        .unwrap();
+    assert!(child.wait().unwrap().success(), "update-key failed");
+
+    let data = stored_data(config_path);
+    assert!(
+        data.starts_with(b"ZPL\x01\x00"),
+        "Expected an uncompressed payload"
+    );
+    let text = String::from_utf8_lossy(&data);
+    assert!(text.contains("application/json"));
+    assert!(text.ends_with(r#"{"seed": "words"}"#));
+}
+
+#[test]
+fn test_update_key_encrypts_with_passphrase() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let file_path = dir.path().join("letter.txt");
+    fs::write(&file_path, "for the lawyer").unwrap();
+    let passphrase_path = dir.path().join("passphrase.txt");
+    fs::write(&passphrase_path, "correct horse battery staple\n").unwrap();
+    create_mock_key(config_path, "passphrase_key");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "contract",
+            "update-key",
+            "--key-id",
+            "passphrase_key",
+            "--plaintext-file",
+            file_path.to_str().unwrap(),
+            "--encrypt-with-passphrase",
+            "--passphrase-file",
+            passphrase_path.to_str().unwrap(),
+            "--timeout",
+            "300",
+            "--mock",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "update-key failed");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("unencrypted"));
+
+    let data = stored_data(config_path);
+    assert!(
+        data.starts_with(b"ZPW\x01"),
+        "Expected passphrase encryption"
+    );
+    assert!(!String::from_utf8_lossy(&data).contains("lawyer"));
+}
+
+fn update_with(config_path: &str, key_id: &str, args: &[&str]) {
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "contract",
+            "update-key",
+            "--key-id",
+            key_id,
+        ])
+        .args(args)
+        .args(["--timeout", "300", "--mock"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "update-key failed");
+}
+
+#[test]
+fn test_update_key_pads_data_to_buckets() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let file_path = dir.path().join("seed.txt");
+    fs::write(&file_path, "x".repeat(300)).unwrap();
+    create_mock_key(config_path, "padded_key");
+
+    update_with(config_path, "padded_key", &["--data", "deadbeef", "--pad"]);
+    let data = stored_data(config_path);
+    assert_eq!(data.len(), 256);
+    assert!(data.starts_with(b"ZPD\x01"));
+
+    update_with(
+        config_path,
+        "padded_key",
+        &[
+            "--plaintext-file",
+            file_path.to_str().unwrap(),
+            "--encrypt-for",
+            "0x038318535b54105d4a7aae60c08fc45f9687181b4fdfc625bd1a753fa7397fed75",
+            "--pad",
+            "512,2k",
+        ],
+    );
+    assert_eq!(stored_data(config_path).len(), 512);
+}
@@ -0,0 +1,148 @@
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_create_key_uses_default_profile() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let key_id = "profile_key";
+
+    let mock = env::var("ZAPHENATH_TEST_MOCK").unwrap_or_else(|_| "true".into()) == "true";
+    let contract_address = env::var("ZAPHENATH_TEST_CONTRACT")
+        .unwrap_or_else(|_| "0x0000000000000000000000000000000000000001".into());
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let rpc_url = env::var("ZAPHENATH_TEST_RPC").unwrap_or("http://localhost:8545".into());
+
+    // 1. Add a default profile
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "profile",
+            "add",
+            "--name",
+            "team-anvil",
+            "--rpc-url",
+            &rpc_url,
+            "--contract-address",
+            &contract_address,
+            "--private-key-path",
+            &private_key_path,
+            "--default",
+        ])
+        .status()
+        .expect("Failed to run profile add");
+    assert!(status.success(), "profile add failed");
+
+    // 2. Create a key without repeating contract, signer or network flags
+    let mut create = Command::new("cargo");
+    create.args([
+        "run",
+        "--quiet",
+        "--",
+        "--config",
+        config_path.to_str().unwrap(),
+        "contract",
+        "create-key",
+        "--key-id",
+        key_id,
+        "--data",
+        "00ffcc",
+        "--timeout",
+        "120",
+        "--yes",
+    ]);
+    if mock {
+        create.arg("--mock");
+    }
+    assert!(create.status().unwrap().success(), "create-key failed");
+
+    // 3. The new key entry carries the profile's values
+    let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
+    let config_json: Value = serde_json::from_str(&contents).expect("Invalid JSON in config");
+    let entry = config_json["keys"]
+        .as_array()
+        .expect("Config keys should be an array")
+        .iter()
+        .find(|e| e.get("key_id").and_then(Value::as_str) == Some(key_id))
+        .expect("Key entry not found");
+
+    assert_eq!(entry["contract_address"], contract_address.as_str());
+    assert_eq!(entry["rpc_url"], rpc_url.as_str());
+}
+
+#[test]
+fn test_unknown_profile_fails() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "--profile",
+            "does-not-exist",
+            "contract",
+            "create-key",
+            "--key-id",
+            "k",
+            "--data",
+            "00",
+            "--timeout",
+            "1",
+            "--mock",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success(), "unknown profile should fail");
+    assert!(
+        stderr.contains("not found"),
+        "Expected error about missing profile, got: {stderr}"
+    );
+}
+
+#[test]
+fn test_profile_list_shows_every_kind_of_signer() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let zaph = |args: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    for (name, signer) in [
+        ("env", ["--private-key-env", "ZAPH_KEY"]),
+        ("command", ["--signer-command", "my-signer"]),
+    ] {
+        let output = zaph(&[&["profile", "add", "--name", name][..], &signer[..]].concat());
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    let output = zaph(&["profile", "list"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("signer=env $ZAPH_KEY"), "{stdout}");
+    assert!(stdout.contains("signer=command my-signer"), "{stdout}");
+}
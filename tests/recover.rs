@@ -0,0 +1,234 @@
+use std::process::Command;
+use std::time::Duration;
+use std::{env, fs, thread};
+use tempfile::tempdir;
+
+// Anvil's first two default accounts; the first is the usual test signer.
+const FIRST_PUBKEY: &str = "0x038318535b54105d4a7aae60c08fc45f9687181b4fdfc625bd1a753fa7397fed75";
+const SECOND_KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+const SECOND_PUBKEY: &str = "0x02ba5734d8f7091719471e7f7ed6b9df170dc70cc661ca05e688601ad984f068b0";
+
+fn zaph(config_path: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::new("cargo");
+    cmd.args(["run", "--quiet", "--", "--config", config_path])
+        .args(args);
+    cmd
+}
+
+#[test]
+fn test_create_split_key_and_combine_shares() {
+    let _ = dotenvy::from_filename(".env.test").ok();
+
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let plaintext_path = dir.path().join("secret.txt");
+    fs::write(&plaintext_path, "split me").unwrap();
+    let second_key_path = dir.path().join("custodian.hex");
+    fs::write(&second_key_path, SECOND_KEY).unwrap();
+    let key_id = "split_key";
+
+    let mock = env::var("ZAPHENATH_TEST_MOCK").unwrap_or_else(|_| "true".into()) == "true";
+    let contract_address = env::var("ZAPHENATH_TEST_CONTRACT")
+        .unwrap_or_else(|_| "0x0000000000000000000000000000000000000001".into());
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+    let rpc_url = env::var("ZAPHENATH_TEST_RPC").unwrap_or("http://localhost:8545".into());
+
+    let mut create = zaph(
+        config_path,
+        &[
+            "contract",
+            "create-key",
+            "--key-id",
+            key_id,
+            "--plaintext-file",
+            plaintext_path.to_str().unwrap(),
+            "--encrypt-for",
+            &format!("{FIRST_PUBKEY},{SECOND_PUBKEY}"),
+            "--threshold",
+            "2",
+            "--timeout",
+            "0",
+            "--rpc-url",
+            &rpc_url,
+            "--contract-address",
+            &contract_address,
+            "--private-key-path",
+            &private_key_path,
+            "--yes",
+        ],
+    );
+    if mock {
+        create.arg("--mock");
+    } else {
+        thread::sleep(Duration::from_secs(1)); // Wait for some blocks to be mined
+    }
+    assert!(create.status().unwrap().success(), "key creation failed");
+
+    let mut shares = Vec::new();
+    for decrypt_key in [private_key_path.as_str(), second_key_path.to_str().unwrap()] {
+        let output = zaph(
+            config_path,
+            &[
+                "contract",
+                "read-key",
+                "--key-id",
+                key_id,
+                "--decrypt",
+                "--private-key-path",
+                decrypt_key,
+            ],
+        )
+        .output()
+        .expect("Failed to run read-key");
+        assert!(
+            output.status.success(),
+            "read-key --decrypt failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        shares.push(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+
+    // A share written with --out is taken as a file, and never printed
+    let share_path = dir.path().join("share.bin");
+    let output = zaph(
+        config_path,
+        &[
+            "contract",
+            "read-key",
+            "--key-id",
+            key_id,
+            "--decrypt",
+            "--private-key-path",
+            second_key_path.to_str().unwrap(),
+            "--out",
+            share_path.to_str().unwrap(),
+        ],
+    )
+    .output()
+    .expect("Failed to run read-key");
+    assert!(output.status.success(), "read-key --decrypt --out failed");
+    assert!(output.stdout.is_empty());
+    assert!(share_path.exists());
+
+    let output = zaph(config_path, &["recover", "combine", "--decode"])
+        .args(&shares)
+        .output()
+        .expect("Failed to run recover combine");
+    assert!(output.status.success(), "recover combine failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "split me");
+
+    let output = zaph(config_path, &["recover", "combine", "--decode", &shares[0]])
+        .arg(&share_path)
+        .output()
+        .expect("Failed to run recover combine");
+    assert!(output.status.success(), "recover combine failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "split me");
+}
+
+#[test]
+fn test_update_key_stores_split_shares() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let plaintext_path = dir.path().join("secret.txt");
+    fs::write(&plaintext_path, "split me").unwrap();
+    let private_key_path = env::var("ZAPHENATH_TEST_PRIVKEY").unwrap_or("/dev/null".into());
+
+    let status = zaph(
+        config_path,
+        &[
+            "contract",
+            "create-key",
+            "--key-id",
+            "split_update_key",
+            "--data",
+            "abc123",
+            "--timeout",
+            "300",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--private-key-path",
+            &private_key_path,
+            "--network",
+            "anvil",
+            "--mock",
+        ],
+    )
+    .status()
+    .unwrap();
+    assert!(status.success(), "key creation failed");
+
+    let status = zaph(
+        config_path,
+        &[
+            "contract",
+            "update-key",
+            "--key-id",
+            "split_update_key",
+            "--plaintext-file",
+            plaintext_path.to_str().unwrap(),
+            "--encrypt-for",
+            FIRST_PUBKEY,
+            "--encrypt-for",
+            SECOND_PUBKEY,
+            "--threshold",
+            "2",
+            "--timeout",
+            "300",
+            "--mock",
+        ],
+    )
+    .status()
+    .unwrap();
+    assert!(status.success(), "update-key failed");
+
+    let contents = fs::read_to_string(config_path).unwrap();
+    let config_json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    let data = config_json["keys"][0]["data"].as_str().unwrap();
+    assert!(
+        data.starts_with("5a535301"),
+        "Expected split shares, got: {data}"
+    );
+
+    // More shares than recipients can't be required.
+    let output = zaph(
+        config_path,
+        &[
+            "contract",
+            "update-key",
+            "--key-id",
+            "split_update_key",
+            "--plaintext-file",
+            plaintext_path.to_str().unwrap(),
+            "--encrypt-for",
+            FIRST_PUBKEY,
+            "--threshold",
+            "2",
+            "--timeout",
+            "300",
+            "--mock",
+        ],
+    )
+    .output()
+    .unwrap();
+    assert!(
+        !output.status.success(),
+        "threshold above recipients should fail"
+    );
+}
+
+#[test]
+fn test_combine_rejects_invalid_shares() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+
+    let output = zaph(
+        config_path.to_str().unwrap(),
+        &["recover", "combine", "0xdeadbeef"],
+    )
+    .output()
+    .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a zaph secret share"));
+}
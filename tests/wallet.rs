@@ -0,0 +1,129 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+fn zaph(config_path: &str, args: &[&str]) -> Output {
+    Command::new("cargo")
+        .args(["run", "--quiet", "--", "--config", config_path])
+        .args(args)
+        .env("ZAPHENATH_KEYSTORE_PASSWORD", "test passphrase")
+        .output()
+        .expect("Failed to run zaph")
+}
+
+fn address_in(output: &Output) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("📬 Address: "))
+        .expect("no address printed")
+        .to_string()
+}
+
+#[test]
+fn test_wallet_new_address_and_import() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+    let key_path = dir.path().join("key.hex");
+    let key_path = key_path.to_str().unwrap();
+    let keystore_path = dir.path().join("key.json");
+    let keystore_path = keystore_path.to_str().unwrap();
+
+    let output = zaph(config_path, &["wallet", "new", key_path]);
+    assert!(output.status.success(), "wallet new failed");
+    let address = address_in(&output);
+    let mode = fs::metadata(key_path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600, "key file is not owner-only");
+
+    let output = zaph(config_path, &["wallet", "new", key_path]);
+    assert!(!output.status.success(), "wallet new overwrote a key");
+
+    let output = zaph(config_path, &["wallet", "address", key_path]);
+    assert!(output.status.success(), "wallet address failed");
+    assert_eq!(address_in(&output), address);
+
+    let output = zaph(
+        config_path,
+        &[
+            "wallet",
+            "import",
+            keystore_path,
+            "--private-key-path",
+            key_path,
+        ],
+    );
+    assert!(output.status.success(), "wallet import failed");
+    assert_eq!(address_in(&output), address);
+
+    let output = zaph(config_path, &["wallet", "address", keystore_path]);
+    assert!(output.status.success(), "wallet address on keystore failed");
+    assert_eq!(address_in(&output), address);
+}
+
+#[test]
+fn test_wallet_address_warns_about_loose_permissions() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let key_path = dir.path().join("key.hex");
+    fs::write(
+        &key_path,
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80\n",
+    )
+    .unwrap();
+    fs::set_permissions(&key_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let output = zaph(
+        config_path.to_str().unwrap(),
+        &["wallet", "address", key_path.to_str().unwrap()],
+    );
+    assert!(output.status.success(), "wallet address failed");
+    assert_eq!(
+        address_in(&output),
+        "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("chmod 600"));
+}
+
+#[test]
+fn test_wallet_list_groups_config_signers() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("test_config.json");
+    let config_path = config_path.to_str().unwrap();
+
+    let status = Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--",
+            "--config",
+            config_path,
+            "config",
+            "add",
+            "--key-id",
+            "listed_key",
+            "--contract-address",
+            "0x0000000000000000000000000000000000000001",
+            "--private-key-env",
+            "ZAPH_TEST_WALLET_KEY",
+            "--network",
+            "anvil",
+            "--timeout",
+            "60",
+        ])
+        .env(
+            "ZAPH_TEST_WALLET_KEY",
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .status()
+        .expect("Failed to run config add");
+    assert!(status.success(), "config add failed");
+
+    let output = zaph(config_path, &["wallet", "list"]);
+    assert!(output.status.success(), "wallet list failed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("env $ZAPH_TEST_WALLET_KEY"));
+    assert!(stdout.contains("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
+    assert!(stdout.contains("key 'listed_key'"));
+}